@@ -18,14 +18,18 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use copypasta::ClipboardContext;
+use winit::event::{Event, WindowEvent};
 use winit::event_loop::EventLoopProxy;
+use winit::window::WindowId;
 
 use crate::clipboard::Clipboard;
+use crate::dispatch;
 use crate::error::Error;
 use crate::util;
-use crate::window::WinitEvent;
+use crate::window::{IdleHandle, WinitEvent};
 
 /// A top-level handler that is not associated with any window.
 ///
@@ -58,6 +62,8 @@ pub struct Application {
 /// Platform-independent `Application` state.
 pub(crate) struct State {
     running: bool,
+    focused_window: Option<WindowId>,
+    exit_code: i32,
     pub(crate) event_proxy: Arc<EventLoopProxy<WinitEvent>>,
 }
 
@@ -86,6 +92,8 @@ impl Application {
         util::claim_main_thread();
         let state = Rc::new(RefCell::new(State {
             running: false,
+            focused_window: None,
+            exit_code: 0,
             event_proxy,
         }));
         let clipboard = Clipboard(Rc::new(RefCell::new(
@@ -173,11 +181,91 @@ impl Application {
 
     /// Quit the `Application`.
     ///
-    /// This will cause [`run`] to return control back to the calling function.
+    /// This sends [`WinitEvent::Quit`] to the event loop, which should then exit, with the
+    /// [`exit_code`]. Events that are already queued may still be handled first.
     ///
-    /// [`run`]: #method.run
+    /// [`exit_code`]: #method.exit_code
     pub fn quit(&self) {
-        // self.backend_app.quit()
+        // An error means that the event loop is already gone.
+        let _ = self.state.borrow().event_proxy.send_event(WinitEvent::Quit);
+    }
+
+    /// Set the status the process exits with once the event loop ends.
+    ///
+    /// This applies however the application quits, e.g. when its last window is closed.
+    /// The default is `0`.
+    pub fn set_exit_code(&self, code: i32) {
+        self.state.borrow_mut().exit_code = code;
+    }
+
+    /// Returns the status the process should exit with, see [`set_exit_code`].
+    ///
+    /// [`set_exit_code`]: #method.set_exit_code
+    pub fn exit_code(&self) -> i32 {
+        self.state.borrow().exit_code
+    }
+
+    /// Passes an event from the winit event loop on to the [`WinHandler`] of the window it is
+    /// for, if that window was given one with [`WindowBuilder::set_handler`].
+    ///
+    /// Call this for every event when driving the event loop yourself. Paint, size, scale,
+    /// focus, close, mouse and keyboard events are forwarded, as are idle callbacks scheduled
+    /// through a window's [`IdleHandle`]. Timers fire on the first event after their deadline,
+    /// so wake the loop up with [`next_timer_deadline`] and `ControlFlow::WaitUntil`.
+    ///
+    /// Returns `true` if the event was forwarded to a handler.
+    ///
+    /// [`WinHandler`]: crate::WinHandler
+    /// [`WindowBuilder::set_handler`]: crate::WindowBuilder::set_handler
+    /// [`next_timer_deadline`]: #method.next_timer_deadline
+    pub fn handle_event(&self, event: &Event<WinitEvent>) -> bool {
+        if let Event::WindowEvent { window_id, event } = event {
+            match event {
+                WindowEvent::Focused(focused) => self.record_focus(*window_id, *focused),
+                WindowEvent::Destroyed => self.record_focus(*window_id, false),
+                _ => (),
+            }
+        }
+        dispatch::handle_event(event)
+    }
+
+    /// Returns when the next timer of a window with a handler is due, if one is pending.
+    ///
+    /// See [`handle_event`].
+    ///
+    /// [`handle_event`]: #method.handle_event
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        dispatch::next_timer_deadline()
+    }
+
+    /// Returns the id of the window that has keyboard focus, if it is one of ours.
+    ///
+    /// This is what a command that isn't aimed at any particular window, like "close
+    /// window" from the application menu, should act on.
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.state.borrow().focused_window
+    }
+
+    /// Notes that a window gained or lost focus, for [`focused_window`].
+    ///
+    /// [`handle_event`] calls this; it only needs calling directly when `winit`'s focus
+    /// events aren't passed on to [`handle_event`].
+    ///
+    /// [`focused_window`]: #method.focused_window
+    /// [`handle_event`]: #method.handle_event
+    #[doc(hidden)]
+    pub fn record_focus(&self, window_id: WindowId, focused: bool) {
+        let mut state = self.state.borrow_mut();
+        if focused {
+            state.focused_window = Some(window_id);
+        } else if state.focused_window == Some(window_id) {
+            state.focused_window = None;
+        }
+    }
+
+    /// Returns a handle that can enqueue tasks on the runloop, even when no window is open.
+    pub fn get_idle_handle(&self) -> IdleHandle {
+        IdleHandle(self.state.borrow().event_proxy.clone(), None)
     }
 
     /// Returns a handle to the system clipboard.
@@ -0,0 +1,337 @@
+// Copyright 2022 The Druid Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Routing winit events to the [`WinHandler`]s set with [`WindowBuilder::set_handler`].
+//!
+//! [`WindowBuilder::set_handler`]: crate::WindowBuilder::set_handler
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
+
+use winit::event::{ElementState, Event, WindowEvent};
+use winit::window::WindowId;
+
+use crate::keyboard::{
+    winit_key_with_text, winit_mods_with_text, winit_native_code, KeyCode, KeyEvent, KeyState,
+    Modifiers,
+};
+use crate::kurbo::{Point, Size, Vec2};
+use crate::mouse::{wheel_delta, MouseButton, MouseButtons, MouseEvent, SCROLL_LINE_DISTANCE};
+use crate::scale::Scale;
+use crate::window::{TimerToken, WinHandler, WindowHandle, WinitEvent};
+
+/// A connected handler, and the input state winit doesn't repeat on every event.
+struct Connected {
+    handler: Box<dyn WinHandler>,
+    /// The handle of the window, which the handler keeps alive anyway.
+    handle: WindowHandle,
+    scale: f64,
+    mods: Modifiers,
+    buttons: MouseButtons,
+    pos: Point,
+    /// Keys whose press was a menu hotkey, so their release is dropped too.
+    hotkeys_down: HashSet<KeyCode>,
+}
+
+thread_local! {
+    static HANDLERS: RefCell<HashMap<WindowId, Connected>> = RefCell::new(HashMap::new());
+    /// Timers of handled windows that haven't fired yet, by deadline.
+    static TIMERS: RefCell<BTreeMap<Instant, Vec<(WindowId, TimerToken)>>> =
+        RefCell::new(BTreeMap::new());
+}
+
+/// Returns the deadline of the next timer of a handled window.
+pub(crate) fn next_timer_deadline() -> Option<Instant> {
+    TIMERS.with(|timers| timers.borrow().keys().next().copied())
+}
+
+fn has_handler(window_id: WindowId) -> bool {
+    HANDLERS.with(|handlers| handlers.borrow().contains_key(&window_id))
+}
+
+/// Calls [`WinHandler::timer`] for every timer whose deadline has passed.
+///
+/// Returns `true` if any timer fired.
+fn fire_due_timers() -> bool {
+    let now = Instant::now();
+    let due = TIMERS.with(|timers| {
+        let mut timers = timers.borrow_mut();
+        let pending = timers.split_off(&now);
+        std::mem::replace(&mut *timers, pending)
+    });
+    let mut fired = false;
+    for (window_id, token) in due.into_values().flatten() {
+        with_handler(window_id, |connected| {
+            if !connected.handle.take_cancelled_timer(token) {
+                connected.handler.timer(token);
+                fired = true;
+            }
+        });
+    }
+    fired
+}
+
+pub(crate) fn register(handle: &WindowHandle, handler: Box<dyn WinHandler>) {
+    let connected = Connected {
+        handler,
+        handle: handle.clone(),
+        scale: handle.get_scale(),
+        mods: Modifiers::empty(),
+        buttons: MouseButtons::new(),
+        pos: Point::ZERO,
+        hotkeys_down: HashSet::new(),
+    };
+    HANDLERS.with(|handlers| handlers.borrow_mut().insert(handle.id(), connected));
+}
+
+/// Calls `f` with the window's handler, if it has one.
+///
+/// The handler is taken out of the map for the duration of the call, because it may well
+/// build another window with a handler of its own.
+fn with_handler(window_id: WindowId, f: impl FnOnce(&mut Connected)) {
+    let connected = HANDLERS.with(|handlers| handlers.borrow_mut().remove(&window_id));
+    if let Some(mut connected) = connected {
+        f(&mut connected);
+        if let Some(token) = connected.handle.take_text_focus_request() {
+            connected.handler.text_field_focus_changed(token);
+        }
+        update_ime_cursor_area(&mut connected);
+        HANDLERS.with(|handlers| handlers.borrow_mut().insert(window_id, connected));
+    }
+}
+
+/// Forwards `event` to the [`WinHandler`] of the window it's for.
+///
+/// Timers are held until their deadline and fired on the first event after it.
+///
+/// Returns `true` if the window has a handler.
+pub(crate) fn handle_event(event: &Event<WinitEvent>) -> bool {
+    match event {
+        Event::NewEvents(_) => fire_due_timers(),
+        Event::UserEvent(WinitEvent::Timer(window_id, token, deadline)) => {
+            if !has_handler(*window_id) {
+                return false;
+            }
+            TIMERS.with(|timers| {
+                timers
+                    .borrow_mut()
+                    .entry(*deadline)
+                    .or_insert_with(Vec::new)
+                    .push((*window_id, *token))
+            });
+            // A timer may be requested with a deadline that has already passed.
+            fire_due_timers();
+            true
+        }
+        Event::UserEvent(WinitEvent::CancelTimer(window_id, token)) => {
+            let mut found = false;
+            with_handler(*window_id, |connected| {
+                connected.handle.take_cancelled_timer(*token);
+                found = true;
+            });
+            TIMERS.with(|timers| {
+                timers.borrow_mut().retain(|_, tokens| {
+                    tokens.retain(|t| *t != (*window_id, *token));
+                    !tokens.is_empty()
+                })
+            });
+            found
+        }
+        Event::UserEvent(WinitEvent::Idle(Some(window_id), token)) => {
+            let mut found = false;
+            with_handler(*window_id, |connected| {
+                connected.handler.idle(*token);
+                found = true;
+            });
+            found
+        }
+        Event::WindowEvent { window_id, event } => {
+            let window_id = *window_id;
+            let mut found = false;
+            if let WindowEvent::Destroyed = event {
+                let connected = HANDLERS.with(|handlers| handlers.borrow_mut().remove(&window_id));
+                if let Some(mut connected) = connected {
+                    TIMERS.with(|timers| {
+                        timers.borrow_mut().retain(|_, tokens| {
+                            tokens.retain(|(id, _)| *id != window_id);
+                            !tokens.is_empty()
+                        })
+                    });
+                    connected.handler.destroy();
+                    found = true;
+                }
+            } else {
+                with_handler(window_id, |connected| {
+                    handle_window_event(connected, event);
+                    found = true;
+                });
+            }
+            found
+        }
+        Event::RedrawRequested(window_id) => {
+            let mut found = false;
+            with_handler(*window_id, |connected| {
+                connected.handler.prepare_paint();
+                connected.handle.take_invalid_region();
+                connected.handler.paint();
+                found = true;
+            });
+            found
+        }
+        _ => false,
+    }
+}
+
+/// Moves the IME cursor area to the caret, if the handler said it moved with
+/// [`WindowHandle::update_text_field`].
+fn update_ime_cursor_area(connected: &mut Connected) {
+    let token = match connected.handle.take_moved_text_field() {
+        Some(token) => token,
+        None => return,
+    };
+    let input_handler = connected.handler.acquire_input_lock(token, false);
+    let rect = input_handler.slice_bounding_box(input_handler.selection().range());
+    drop(input_handler);
+    connected.handler.release_input_lock(token);
+    if let Some(rect) = rect {
+        connected
+            .handle
+            .set_ime_cursor_area(rect.origin(), rect.size());
+    }
+}
+
+fn handle_window_event(connected: &mut Connected, event: &WindowEvent) {
+    let handler = &mut connected.handler;
+    match event {
+        WindowEvent::ScaleFactorChanged {
+            scale_factor,
+            new_inner_size,
+        } => {
+            connected.scale = *scale_factor;
+            handler.scale(Scale::new(*scale_factor, *scale_factor));
+            let size = new_inner_size.to_logical::<f64>(*scale_factor);
+            handler.size(Size::new(size.width, size.height));
+        }
+        WindowEvent::Resized(size) => {
+            let size = size.to_logical::<f64>(connected.scale);
+            handler.size(Size::new(size.width, size.height));
+        }
+        WindowEvent::CloseRequested => handler.request_close(),
+        WindowEvent::Focused(true) => handler.got_focus(),
+        WindowEvent::Focused(false) => handler.lost_focus(),
+        WindowEvent::ModifiersChanged(winit_mods) => {
+            let mut mods = Modifiers::empty();
+            mods.set(Modifiers::SHIFT, winit_mods.shift_key());
+            mods.set(Modifiers::CONTROL, winit_mods.control_key());
+            mods.set(Modifiers::ALT, winit_mods.alt_key());
+            mods.set(Modifiers::META, winit_mods.super_key());
+            connected.mods = mods;
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            let position = position.to_logical::<f64>(connected.scale);
+            connected.pos = Point::new(position.x, position.y);
+            let event = mouse_event(connected, MouseButton::None, Vec2::ZERO);
+            connected.handler.mouse_move(&event);
+        }
+        WindowEvent::CursorLeft { .. } => handler.mouse_leave(),
+        // Refresh hover state even if the mouse comes back without moving.
+        WindowEvent::CursorEntered { .. } => {
+            let event = mouse_event(connected, MouseButton::None, Vec2::ZERO);
+            connected.handler.mouse_move(&event);
+        }
+        WindowEvent::MouseInput {
+            state: mouse_state,
+            button,
+            ..
+        } => {
+            let button = match button {
+                winit::event::MouseButton::Left => MouseButton::Left,
+                winit::event::MouseButton::Right => MouseButton::Right,
+                winit::event::MouseButton::Middle => MouseButton::Middle,
+                winit::event::MouseButton::Other(_) => MouseButton::None,
+            };
+            match mouse_state {
+                ElementState::Pressed => connected.buttons.insert(button),
+                ElementState::Released => connected.buttons.remove(button),
+            }
+            let event = mouse_event(connected, button, Vec2::ZERO);
+            match mouse_state {
+                ElementState::Pressed => connected.handler.mouse_down(&event),
+                ElementState::Released => connected.handler.mouse_up(&event),
+            }
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+            let delta = wheel_delta(
+                *delta,
+                connected.scale,
+                SCROLL_LINE_DISTANCE,
+                connected.mods,
+            );
+            let event = mouse_event(connected, MouseButton::None, delta);
+            connected.handler.wheel(&event);
+        }
+        WindowEvent::TouchpadMagnify { delta, .. } => handler.zoom(*delta),
+        WindowEvent::KeyboardInput {
+            event,
+            is_synthetic,
+            ..
+        } => {
+            let mut key_event = KeyEvent::default();
+            key_event.state = match event.state {
+                ElementState::Pressed => KeyState::Down,
+                ElementState::Released => KeyState::Up,
+            };
+            key_event.key = winit_key_with_text(event.logical_key.clone(), event.text);
+            key_event.code = event.physical_key;
+            key_event.native_code = winit_native_code(event.physical_key);
+            key_event.mods = winit_mods_with_text(connected.mods, event.text);
+            key_event.repeat = event.repeat;
+            key_event.synthetic = *is_synthetic;
+            match key_event.state {
+                KeyState::Down => {
+                    let command = if key_event.is_composing {
+                        None
+                    } else {
+                        connected.handle.menu_command_for_key(&key_event)
+                    };
+                    if let Some(id) = command {
+                        connected.hotkeys_down.insert(key_event.code);
+                        handler.command(id);
+                    } else {
+                        handler.key_down(key_event);
+                    }
+                }
+                KeyState::Up => {
+                    if !connected.hotkeys_down.remove(&key_event.code) {
+                        handler.key_up(key_event);
+                    }
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn mouse_event(connected: &Connected, button: MouseButton, wheel_delta: Vec2) -> MouseEvent {
+    MouseEvent {
+        pos: connected.pos,
+        buttons: connected.buttons,
+        mods: connected.mods,
+        count: 0,
+        focus: false,
+        button,
+        wheel_delta,
+    }
+}
@@ -16,8 +16,6 @@
 
 use std::borrow::Borrow;
 
-use tracing::warn;
-
 use crate::{IntoKey, KbKey, KeyEvent, Modifiers};
 
 // TODO: fix docstring
@@ -54,6 +52,18 @@ use crate::{IntoKey, KbKey, KeyEvent, Modifiers};
 /// assert!(!hotkey.matches(KeyEvent::for_test(RawMods::Ctrl, KbKey::ArrowLeft)));
 /// ```
 ///
+/// Characters match regardless of case, and lock keys such as Caps Lock and Num Lock
+/// are ignored:
+///
+/// ```
+/// use druid_shell::{HotKey, KeyEvent, Modifiers};
+///
+/// let hotkey = HotKey::new(Modifiers::CONTROL | Modifiers::SHIFT, "p");
+///
+/// let mods = Modifiers::CONTROL | Modifiers::SHIFT | Modifiers::NUM_LOCK;
+/// assert!(hotkey.matches(KeyEvent::for_test(mods, "P")));
+/// ```
+///
 /// [`SysMods`]: enum.SysMods.html
 #[derive(Debug, Clone, PartialEq)]
 pub struct HotKey {
@@ -65,11 +75,12 @@ impl HotKey {
     /// Create a new hotkey.
     ///
     /// The first argument describes the keyboard modifiers. This can be `None`,
-    /// or an instance of either [`SysMods`], or [`RawMods`]. [`SysMods`] unify the
-    /// 'Command' key on macOS with the 'Ctrl' key on other platforms.
+    /// or an instance of either [`SysMods`], [`RawMods`] or [`Modifiers`]. [`SysMods`] unify
+    /// the 'Command' key on macOS with the 'Ctrl' key on other platforms. Only Shift, Ctrl,
+    /// Alt and Meta are taken from a [`Modifiers`].
     ///
     /// The second argument describes the non-modifier key. This can be either
-    /// a `&str` or a [`KbKey`]; the former is merely a convenient
+    /// a `&str`, a `char` or a [`KbKey`]; the former two are merely a convenient
     /// shorthand for `KbKey::Character()`.
     ///
     /// # Examples
@@ -89,32 +100,28 @@ impl HotKey {
             mods: mods.into().unwrap_or(RawMods::None),
             key: key.into_key(),
         }
-        .warn_if_needed()
-    }
-
-    //TODO: figure out if we need to be normalizing case or something?
-    fn warn_if_needed(self) -> Self {
-        if let KbKey::Character(s) = &self.key {
-            let km: Modifiers = self.mods.into();
-            if km.shift() && s.chars().any(|c| c.is_lowercase()) {
-                warn!(
-                    "warning: HotKey {:?} includes shift, but text is lowercase. \
-                     Text is matched literally; this may cause problems.",
-                    &self
-                );
-            }
-        }
-        self
     }
 
     /// Returns `true` if this [`KeyEvent`] matches this `HotKey`.
     ///
+    /// The modifiers have to be the same, except for lock keys which are ignored.
+    /// `KbKey::Character` keys are compared case-insensitively, since whether Shift or
+    /// Caps Lock changes the case of the character depends on the keyboard layout.
+    ///
     /// [`KeyEvent`]: KeyEvent
     pub fn matches(&self, event: impl Borrow<KeyEvent>) -> bool {
         // Should be a const but const bit_or doesn't work here.
         let base_mods = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::META;
         let event = event.borrow();
-        self.mods == event.mods & base_mods && self.key == event.key
+        if self.mods != event.mods & base_mods {
+            return false;
+        }
+        match (&self.key, &event.key) {
+            (KbKey::Character(ours), KbKey::Character(theirs)) => {
+                ours.to_lowercase() == theirs.to_lowercase()
+            }
+            (ours, theirs) => ours == theirs,
+        }
     }
 }
 
@@ -216,6 +223,35 @@ impl From<RawMods> for Modifiers {
     }
 }
 
+impl From<Modifiers> for RawMods {
+    fn from(src: Modifiers) -> RawMods {
+        match (src.alt(), src.ctrl(), src.meta(), src.shift()) {
+            (false, false, false, false) => RawMods::None,
+            (true, false, false, false) => RawMods::Alt,
+            (false, true, false, false) => RawMods::Ctrl,
+            (false, false, true, false) => RawMods::Meta,
+            (false, false, false, true) => RawMods::Shift,
+            (true, true, false, false) => RawMods::AltCtrl,
+            (true, false, true, false) => RawMods::AltMeta,
+            (true, false, false, true) => RawMods::AltShift,
+            (false, true, true, false) => RawMods::CtrlMeta,
+            (false, true, false, true) => RawMods::CtrlShift,
+            (false, false, true, true) => RawMods::MetaShift,
+            (true, true, true, false) => RawMods::AltCtrlMeta,
+            (true, false, true, true) => RawMods::AltMetaShift,
+            (true, true, false, true) => RawMods::AltCtrlShift,
+            (false, true, true, true) => RawMods::CtrlMetaShift,
+            (true, true, true, true) => RawMods::AltCtrlMetaShift,
+        }
+    }
+}
+
+impl From<Modifiers> for Option<RawMods> {
+    fn from(src: Modifiers) -> Option<RawMods> {
+        Some(src.into())
+    }
+}
+
 // we do this so that HotKey::new can accept `None` as an initial argument.
 impl From<SysMods> for Option<RawMods> {
     fn from(src: SysMods) -> Option<RawMods> {
@@ -245,3 +281,37 @@ impl From<SysMods> for RawMods {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_match_in_either_case() {
+        let save_as = HotKey::new(RawMods::CtrlShift, "s");
+        assert!(save_as.matches(KeyEvent::for_test(
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            "S"
+        )));
+        assert!(save_as.matches(KeyEvent::for_test(
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            "s"
+        )));
+        assert!(!save_as.matches(KeyEvent::for_test(Modifiers::CONTROL, "s")));
+    }
+
+    #[test]
+    fn lock_keys_are_ignored() {
+        let save = HotKey::new(RawMods::Ctrl, "s");
+        let mods = Modifiers::CONTROL | Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK;
+        assert!(save.matches(KeyEvent::for_test(mods, "S")));
+        assert!(!save.matches(KeyEvent::for_test(mods | Modifiers::ALT, "s")));
+    }
+
+    #[test]
+    fn named_keys_match_exactly() {
+        let close = HotKey::new(None, KbKey::Escape);
+        assert!(close.matches(KeyEvent::for_test(Modifiers::empty(), KbKey::Escape)));
+        assert!(!close.matches(KeyEvent::for_test(Modifiers::empty(), KbKey::Enter)));
+    }
+}
@@ -19,9 +19,11 @@
 #![allow(clippy::trivially_copy_pass_by_ref)]
 
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use std::time::{Duration, Instant};
 
 pub use keyboard_types::{Code, KeyState, Location};
-use winit::keyboard::{KeyCode, NativeKeyCode};
+pub use winit::keyboard::KeyCode;
+use winit::keyboard::NativeKeyCode;
 
 /// The meaning (mapped value) of a keypress.
 pub type KbKey = keyboard_types::Key;
@@ -42,6 +44,9 @@ pub struct KeyEvent {
     pub key: KbKey,
     /// Physical key position.
     pub code: KeyCode,
+    /// The raw platform scancode, for keys that have no [`KeyCode`] of their own
+    /// (`code` is then [`KeyCode::Unidentified`]).
+    pub native_code: Option<u32>,
     /// Location for keys with multiple instances on common keyboards.
     pub location: Location,
     /// Flags for pressed modifier keys.
@@ -51,6 +56,9 @@ pub struct KeyEvent {
     /// Events with this flag should be ignored in a text editor
     /// and instead composition events should be used.
     pub is_composing: bool,
+    /// True if the platform made this event up instead of the user pressing a key,
+    /// e.g. the key releases sent for held keys when the window loses focus.
+    pub synthetic: bool,
 }
 
 /// The modifiers.
@@ -65,8 +73,8 @@ pub struct Modifiers(keyboard_types::Modifiers);
 
 /// A convenience trait for creating Key objects.
 ///
-/// This trait is implemented by [`KbKey`] itself and also strings, which are
-/// converted into the `Character` variant. It is defined this way and not
+/// This trait is implemented by [`KbKey`] itself and also strings and `char`s, which
+/// are converted into the `Character` variant. It is defined this way and not
 /// using the standard `Into` mechanism because `KbKey` is a type in an external
 /// crate.
 ///
@@ -76,6 +84,50 @@ pub trait IntoKey {
 }
 
 impl KeyEvent {
+    /// The text this key press should insert, if any.
+    ///
+    /// This is the string of a [`KbKey::Character`] key, which includes text composed
+    /// with dead keys. Text committed by an IME arrives through the focused text field's
+    /// input handler, or, for a window without text fields, as a key press of its own.
+    ///
+    /// ```
+    /// use druid_shell::{KbKey, KeyEvent, Modifiers};
+    ///
+    /// assert_eq!(KeyEvent::for_test(Modifiers::SHIFT, "A").text(), Some("A"));
+    /// assert_eq!(KeyEvent::for_test(Modifiers::empty(), KbKey::Enter).text(), None);
+    /// ```
+    pub fn text(&self) -> Option<&str> {
+        match &self.key {
+            KbKey::Character(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The physical key, by its position on a US keyboard.
+    ///
+    /// This ignores the user's keyboard layout by design: the key left of `S` is
+    /// [`KeyCode::KeyA`] on a QWERTY keyboard, and still is on an AZERTY keyboard, where it
+    /// says `Q`. That is what games want for movement keys; use [`key`] for shortcuts and
+    /// anything else that follows the labels on the keys.
+    ///
+    /// [`key`]: KeyEvent::key
+    pub fn physical(&self) -> KeyCode {
+        self.code
+    }
+
+    /// Returns `true` if this is an event for the physical key `code`, whatever the layout.
+    ///
+    /// ```
+    /// use druid_shell::{KeyCode, KeyEvent, Location, Modifiers};
+    ///
+    /// let event = KeyEvent::for_test_with(Modifiers::empty(), "z", KeyCode::KeyW, Location::Standard);
+    /// // Moves forward on an AZERTY keyboard too.
+    /// assert!(event.is_physical(KeyCode::KeyW));
+    /// ```
+    pub fn is_physical(&self, code: KeyCode) -> bool {
+        self.code == code
+    }
+
     #[doc(hidden)]
     /// Create a key event for testing purposes.
     pub fn for_test(mods: impl Into<Modifiers>, key: impl IntoKey) -> KeyEvent {
@@ -84,11 +136,97 @@ impl KeyEvent {
         KeyEvent {
             key,
             code: KeyCode::Unidentified(NativeKeyCode::Unidentified),
+            native_code: None,
             location: Location::Standard,
             state: KeyState::Down,
             mods,
             is_composing: false,
             repeat: false,
+            synthetic: false,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Create a key event for testing purposes, with the given physical key and location.
+    ///
+    /// This is useful for testing code that tells apart e.g. the left and right Shift
+    /// keys, or the numpad digits from the ones in the top row.
+    pub fn for_test_with(
+        mods: impl Into<Modifiers>,
+        key: impl IntoKey,
+        code: KeyCode,
+        location: Location,
+    ) -> KeyEvent {
+        KeyEvent {
+            code,
+            location,
+            ..KeyEvent::for_test(mods, key)
+        }
+    }
+}
+
+/// Slows the platform's key auto-repeat down to a rate of your choosing.
+///
+/// Feed every key event to [`accept`], and only act on those it returns `true` for.
+/// Holding a key then acts once on the press, once more after the initial delay and
+/// then once per interval, however fast the platform repeats it.
+///
+/// ```
+/// use std::time::Duration;
+/// use druid_shell::{KbKey, KeyEvent, KeyRepeat, Modifiers};
+///
+/// let mut repeat = KeyRepeat::new(Duration::from_millis(400), Duration::from_millis(100));
+/// let mut event = KeyEvent::for_test(Modifiers::empty(), KbKey::ArrowUp);
+/// assert!(repeat.accept(&event));
+/// event.repeat = true;
+/// // Too soon after the press.
+/// assert!(!repeat.accept(&event));
+/// ```
+///
+/// [`accept`]: KeyRepeat::accept
+#[derive(Debug, Clone)]
+pub struct KeyRepeat {
+    delay: Duration,
+    interval: Duration,
+    /// The key being held, and when it may next repeat.
+    held: Option<(KbKey, Instant)>,
+}
+
+impl KeyRepeat {
+    /// Create a filter that repeats `delay` after a key is pressed, and then every
+    /// `interval`.
+    pub fn new(delay: Duration, interval: Duration) -> KeyRepeat {
+        KeyRepeat {
+            delay,
+            interval,
+            held: None,
+        }
+    }
+
+    /// Returns `false` if `event` is an auto-repeat that should be skipped to keep to
+    /// the configured rate. Key presses and releases are always accepted.
+    pub fn accept(&mut self, event: &KeyEvent) -> bool {
+        let now = Instant::now();
+        match event.state {
+            KeyState::Up => {
+                if matches!(&self.held, Some((key, _)) if *key == event.key) {
+                    self.held = None;
+                }
+                true
+            }
+            KeyState::Down => match &mut self.held {
+                Some((key, next)) if event.repeat && *key == event.key => {
+                    if now < *next {
+                        return false;
+                    }
+                    *next = now + self.interval;
+                    true
+                }
+                _ => {
+                    self.held = Some((event.key.clone(), now + self.delay));
+                    true
+                }
+            },
         }
     }
 }
@@ -155,6 +293,26 @@ impl Modifiers {
     pub fn set(&mut self, other: Modifiers, value: bool) {
         self.0.set(other.0, value)
     }
+
+    /// Returns these modifiers without the lock keys (Caps Lock, Num Lock, Scroll Lock,
+    /// Fn Lock and Symbol Lock).
+    ///
+    /// Compare the result when checking for a keyboard shortcut, so that it keeps working
+    /// while e.g. Num Lock is on:
+    ///
+    /// ```
+    /// use druid_shell::Modifiers;
+    ///
+    /// let mods = Modifiers::CONTROL | Modifiers::NUM_LOCK;
+    /// assert_eq!(mods.without_locks(), Modifiers::CONTROL);
+    /// ```
+    pub fn without_locks(self) -> Modifiers {
+        self & !(Modifiers::CAPS_LOCK
+            | Modifiers::NUM_LOCK
+            | Modifiers::SCROLL_LOCK
+            | Modifiers::FN_LOCK
+            | Modifiers::SYMBOL_LOCK)
+    }
 }
 
 impl BitAnd for Modifiers {
@@ -222,6 +380,66 @@ impl IntoKey for &str {
     }
 }
 
+impl IntoKey for String {
+    fn into_key(self) -> KbKey {
+        KbKey::Character(self)
+    }
+}
+
+impl IntoKey for char {
+    fn into_key(self) -> KbKey {
+        KbKey::Character(self.to_string())
+    }
+}
+
+/// Converts the key of a `winit` key event, using the `text` it produced where that is
+/// what the user meant to type.
+///
+/// After a dead key, such as `´` on a US-International layout, the next key press still
+/// has the plain `e` as its logical key, but `é` as its text. Such a key press comes out
+/// as `KbKey::Character("é")`; the dead key itself stays a [`KbKey::Dead`].
+pub fn winit_key_with_text(input: winit::keyboard::Key<'static>, text: Option<&str>) -> KbKey {
+    match (&input, text) {
+        (winit::keyboard::Key::Character(_), Some(text))
+            if !text.is_empty() && !text.chars().any(char::is_control) =>
+        {
+            KbKey::Character(text.to_string())
+        }
+        _ => winit_key(input),
+    }
+}
+
+/// Returns the modifiers of a key press that produced `text`.
+///
+/// Windows reports AltGr as Control and Alt held together. When those produce text,
+/// e.g. `@` from AltGr+Q on a German layout, they are replaced by
+/// [`Modifiers::ALT_GRAPH`], so the press types its text instead of looking like a
+/// shortcut.
+pub fn winit_mods_with_text(mods: Modifiers, text: Option<&str>) -> Modifiers {
+    let typed = text.map_or(false, |text| {
+        !text.is_empty() && !text.chars().any(char::is_control)
+    });
+    if typed && mods.ctrl() && mods.alt() {
+        let mut mods = mods;
+        mods.set(Modifiers::CONTROL | Modifiers::ALT, false);
+        mods.set(Modifiers::ALT_GRAPH, true);
+        mods
+    } else {
+        mods
+    }
+}
+
+/// Returns the raw platform scancode carried by an unidentified physical key.
+pub fn winit_native_code(code: KeyCode) -> Option<u32> {
+    match code {
+        KeyCode::Unidentified(NativeKeyCode::Android(code))
+        | KeyCode::Unidentified(NativeKeyCode::Xkb(code)) => Some(code),
+        KeyCode::Unidentified(NativeKeyCode::MacOS(code))
+        | KeyCode::Unidentified(NativeKeyCode::Windows(code)) => Some(code.into()),
+        _ => None,
+    }
+}
+
 pub fn winit_key(input: winit::keyboard::Key<'static>) -> KbKey {
     match input {
         winit::keyboard::Key::Character(c) => KbKey::Character(c.to_string()),
@@ -535,3 +753,35 @@ pub fn winit_key(input: winit::keyboard::Key<'static>) -> KbKey {
         _ => KbKey::Unidentified,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn altgr_text_is_not_a_shortcut() {
+        let ctrl_alt = Modifiers::CONTROL | Modifiers::ALT;
+        assert_eq!(
+            winit_mods_with_text(ctrl_alt, Some("@")),
+            Modifiers::ALT_GRAPH
+        );
+        // Shift stays, e.g. for AltGr+Shift on layouts that use it.
+        assert_eq!(
+            winit_mods_with_text(ctrl_alt | Modifiers::SHIFT, Some("|")),
+            Modifiers::ALT_GRAPH | Modifiers::SHIFT
+        );
+    }
+
+    #[test]
+    fn ctrl_alt_without_text_is_a_shortcut() {
+        let ctrl_alt = Modifiers::CONTROL | Modifiers::ALT;
+        assert_eq!(winit_mods_with_text(ctrl_alt, None), ctrl_alt);
+        assert_eq!(winit_mods_with_text(ctrl_alt, Some("")), ctrl_alt);
+        assert_eq!(winit_mods_with_text(ctrl_alt, Some("\u{7f}")), ctrl_alt);
+        // Only Control and Alt together can be AltGr.
+        assert_eq!(
+            winit_mods_with_text(Modifiers::CONTROL, Some("a")),
+            Modifiers::CONTROL
+        );
+    }
+}
@@ -58,11 +58,13 @@ mod application;
 mod clipboard;
 mod common_util;
 mod dialog;
+mod dispatch;
 mod error;
 mod hotkey;
 mod keyboard;
 mod menu;
 mod mouse;
+mod raw_handle;
 mod region;
 mod scale;
 mod screen;
@@ -77,15 +79,22 @@ pub use common_util::Counter;
 pub use dialog::{FileDialogOptions, FileInfo, FileSpec};
 pub use error::Error;
 pub use hotkey::{HotKey, RawMods, SysMods};
-pub use keyboard::{winit_key, Code, IntoKey, KbKey, KeyEvent, KeyState, Location, Modifiers};
+pub use keyboard::{
+    winit_key, winit_key_with_text, winit_mods_with_text, winit_native_code, Code, IntoKey, KbKey,
+    KeyCode, KeyEvent, KeyRepeat, KeyState, Location, Modifiers,
+};
 pub use menu::Menu;
-pub use mouse::{Cursor, CursorDesc, MouseButton, MouseButtons, MouseEvent};
+pub use mouse::{
+    wheel_delta, Cursor, CursorDesc, CursorGrabMode, MouseButton, MouseButtons, MouseEvent,
+    SCROLL_LINE_DISTANCE,
+};
 pub use region::Region;
 pub use scale::{Scalable, Scale, ScaledArea};
-pub use screen::{Monitor, Screen};
+pub use screen::{Monitor, Screen, VideoMode};
 pub use window::{
-    FileDialogToken, IdleHandle, IdleToken, TextFieldToken, TimerToken, WinHandler, WindowBuilder,
-    WindowHandle, WindowLevel, WindowState, WinitEvent,
+    FileDialogToken, FrameStats, Fullscreen, Icon, IdleHandle, IdleToken, ResizeDirection,
+    TextFieldToken, Theme, TimerToken, UrgencyLevel, WinHandler, WindowBuilder, WindowHandle,
+    WindowLevel, WindowState, WinitEvent,
 };
 
 pub use keyboard_types;
@@ -14,6 +14,7 @@
 
 // use crate::backend::menu as backend;
 use crate::hotkey::HotKey;
+use crate::keyboard::KeyEvent;
 
 /// A menu object.
 ///
@@ -25,12 +26,33 @@ use crate::hotkey::HotKey;
 /// Currently, a menu and its items cannot be changed once created. If you need
 /// to change anything about a menu (for instance, disabling or selecting items)
 /// you need to create a new menu with the desired properties.
-pub struct Menu();
+///
+/// winit can't show native menus, so for now a menu only keeps track of its items, and
+/// the [`HotKey`]s of the enabled ones are handled by the window the menu is set on.
+#[derive(Debug, Clone, Default)]
+pub struct Menu(Vec<MenuEntry>);
+
+#[derive(Debug, Clone)]
+enum MenuEntry {
+    Item {
+        id: u32,
+        text: String,
+        key: Option<HotKey>,
+        enabled: bool,
+        selected: bool,
+    },
+    Dropdown {
+        menu: Menu,
+        text: String,
+        enabled: bool,
+    },
+    Separator,
+}
 
 impl Menu {
     /// Create a new empty window or application menu.
     pub fn new() -> Menu {
-        Menu()
+        Menu::default()
     }
 
     /// Create a new empty context menu.
@@ -38,7 +60,7 @@ impl Menu {
     /// Some platforms distinguish between these types of menus, and some
     /// do not.
     pub fn new_for_popup() -> Menu {
-        Menu()
+        Menu::default()
     }
 
     /// Consume this `Menu`, returning the platform menu object.
@@ -47,22 +69,26 @@ impl Menu {
     // }
 
     /// Add the provided `Menu` as a submenu of self, with the provided title.
-    pub fn add_dropdown(&mut self, menu: Menu, text: &str, enabled: bool) {}
+    pub fn add_dropdown(&mut self, menu: Menu, text: &str, enabled: bool) {
+        self.0.push(MenuEntry::Dropdown {
+            menu,
+            text: text.to_string(),
+            enabled,
+        });
+    }
 
     /// Add an item to this menu.
     ///
-    /// The `id` should uniquely identify this item. If the user selects this
-    /// item, the responsible [`WindowHandler`]'s [`command()`] method will
-    /// be called with this `id`. If the `enabled` argument is false, the menu
-    /// item will be grayed out; the hotkey will also be disabled.
-    /// If the `selected` argument is `true`, the menu will have a checkmark
-    /// or platform appropriate equivalent indicating that it is currently selected.
-    /// The `key` argument is an optional [`HotKey`] that will be registered
-    /// with the system.
+    /// The `id` should uniquely identify this item. The `key` argument is an
+    /// optional [`HotKey`]; pressing it calls the responsible [`WinHandler`]'s
+    /// [`command()`] method with this `id`. If the `enabled` argument is false,
+    /// the hotkey is disabled.
     ///
+    /// Menus aren't shown yet, so the text and the `selected` checkmark are only
+    /// kept for when they are.
     ///
-    /// [`WindowHandler`]: trait.WindowHandler.html
-    /// [`command()`]: trait.WindowHandler.html#tymethod.command
+    /// [`WinHandler`]: crate::WinHandler
+    /// [`command()`]: crate::WinHandler::command
     /// [`HotKey`]: struct.HotKey.html
     pub fn add_item(
         &mut self,
@@ -72,8 +98,99 @@ impl Menu {
         enabled: bool,
         selected: bool,
     ) {
+        self.0.push(MenuEntry::Item {
+            id,
+            text: text.to_string(),
+            key: key.cloned(),
+            enabled,
+            selected,
+        });
     }
 
     /// Add a seperator to the menu.
-    pub fn add_separator(&mut self) {}
+    pub fn add_separator(&mut self) {
+        self.0.push(MenuEntry::Separator);
+    }
+
+    /// Returns the `id` of the enabled item whose [`HotKey`] matches the event, looking
+    /// into enabled submenus as well.
+    pub fn command_for_key(&self, event: &KeyEvent) -> Option<u32> {
+        self.0.iter().find_map(|entry| match entry {
+            MenuEntry::Item {
+                id,
+                key: Some(key),
+                enabled: true,
+                ..
+            } if key.matches(event) => Some(*id),
+            MenuEntry::Dropdown {
+                menu,
+                enabled: true,
+                ..
+            } => menu.command_for_key(event),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotkey::RawMods;
+    use crate::keyboard::Modifiers;
+
+    fn menu() -> Menu {
+        let mut edit = Menu::new();
+        edit.add_item(
+            1,
+            "Undo",
+            Some(&HotKey::new(RawMods::Ctrl, "z")),
+            true,
+            false,
+        );
+        edit.add_item(
+            2,
+            "Redo",
+            Some(&HotKey::new(RawMods::Ctrl, "y")),
+            false,
+            false,
+        );
+        let mut disabled = Menu::new();
+        disabled.add_item(
+            3,
+            "Zoom",
+            Some(&HotKey::new(RawMods::Ctrl, "x")),
+            true,
+            false,
+        );
+
+        let mut menu = Menu::new();
+        menu.add_item(
+            4,
+            "Quit",
+            Some(&HotKey::new(RawMods::Ctrl, "q")),
+            true,
+            false,
+        );
+        menu.add_separator();
+        menu.add_dropdown(edit, "Edit", true);
+        menu.add_dropdown(disabled, "View", false);
+        menu
+    }
+
+    #[test]
+    fn hotkeys_are_found_in_submenus() {
+        let menu = menu();
+        let key = |key| KeyEvent::for_test(Modifiers::CONTROL, key);
+        assert_eq!(menu.command_for_key(&key("q")), Some(4));
+        assert_eq!(menu.command_for_key(&key("z")), Some(1));
+        assert_eq!(menu.command_for_key(&key("w")), None);
+    }
+
+    #[test]
+    fn disabled_items_and_submenus_are_skipped() {
+        let menu = menu();
+        let key = |key| KeyEvent::for_test(Modifiers::CONTROL, key);
+        assert_eq!(menu.command_for_key(&key("y")), None);
+        assert_eq!(menu.command_for_key(&key("x")), None);
+    }
 }
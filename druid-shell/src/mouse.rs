@@ -14,10 +14,18 @@
 
 //! Common types for representing mouse events and state
 
+use winit::event::MouseScrollDelta;
+
 use crate::kurbo::{Point, Vec2};
 use crate::piet::ImageBuf;
 use crate::Modifiers;
 
+/// The default height of a scrolled line, or one notch of a plain mouse wheel, in
+/// [display points].
+///
+/// [display points]: crate::Scale
+pub const SCROLL_LINE_DISTANCE: f64 = 32.0;
+
 /// Information about the mouse event.
 ///
 /// Every mouse event can have a new position. There is no guarantee of
@@ -51,10 +59,38 @@ pub struct MouseEvent {
     /// move on scrolling. This polarity is consistent with the
     /// deltaX and deltaY values in a web [WheelEvent].
     ///
+    /// It is in [display points], whether the device reports lines or pixels; see
+    /// [`wheel_delta`].
+    ///
     /// [WheelEvent]: https://w3c.github.io/uievents/#event-type-wheel
+    /// [display points]: crate::Scale
     pub wheel_delta: Vec2,
 }
 
+/// Converts a `winit` scroll delta into a [`MouseEvent::wheel_delta`], in display points.
+///
+/// Mouse wheels report lines, which are multiplied by `line_height`, in display points;
+/// [`SCROLL_LINE_DISTANCE`] is a reasonable default. Trackpads and other precise devices
+/// report physical pixels, which are divided by the window's `scale`. Either way the result
+/// is in display points, so a line scrolls as far as `line_height` pixels of trackpad
+/// movement on a regular screen, and a high-DPI screen doesn't make trackpads scroll faster.
+///
+/// Most platforms leave it to applications to scroll horizontally with a plain wheel
+/// while Shift is held, so that is done here too; macOS already does it by itself.
+pub fn wheel_delta(delta: MouseScrollDelta, scale: f64, line_height: f64, mods: Modifiers) -> Vec2 {
+    match delta {
+        MouseScrollDelta::LineDelta(x, y)
+            if x == 0.0 && mods.shift() && cfg!(not(target_os = "macos")) =>
+        {
+            Vec2::new(-y as f64 * line_height, 0.0)
+        }
+        MouseScrollDelta::LineDelta(x, y) => {
+            Vec2::new(x as f64 * line_height, -y as f64 * line_height)
+        }
+        MouseScrollDelta::PixelDelta(pos) => Vec2::new(pos.x / scale, -pos.y / scale),
+    }
+}
+
 /// An indicator of which mouse button was pressed.
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 #[repr(u8)]
@@ -173,6 +209,44 @@ impl MouseButtons {
         self.0 & buttons.0 == buttons.0
     }
 
+    /// Returns `true` if `button` is the only button in the set.
+    ///
+    /// ```
+    /// use druid_shell::{MouseButton, MouseButtons};
+    ///
+    /// let buttons = MouseButtons::new().with(MouseButton::Left);
+    /// assert!(buttons.is_exactly(MouseButton::Left));
+    /// assert!(!buttons.with(MouseButton::Middle).is_exactly(MouseButton::Left));
+    /// ```
+    #[inline]
+    pub fn is_exactly(self, button: MouseButton) -> bool {
+        self == MouseButtons::new().with(button)
+    }
+
+    /// Returns `true` if all the `buttons` are in the set, e.g. for a chord of the left and
+    /// middle buttons.
+    ///
+    /// This is [`is_superset`] for a slice of buttons. Other buttons may be in the set as
+    /// well; compare with a set built from `buttons` for an exact match.
+    ///
+    /// ```
+    /// use druid_shell::{MouseButton, MouseButtons};
+    ///
+    /// let chord = [MouseButton::Left, MouseButton::Middle];
+    /// let buttons = MouseButtons::new().with(MouseButton::Left);
+    /// assert!(!buttons.contains_all(&chord));
+    /// assert!(buttons.with(MouseButton::Middle).contains_all(&chord));
+    /// ```
+    ///
+    /// [`is_superset`]: MouseButtons::is_superset
+    #[inline]
+    pub fn contains_all(self, buttons: &[MouseButton]) -> bool {
+        let buttons = buttons
+            .iter()
+            .fold(MouseButtons::new(), |set, button| set.with(*button));
+        self.is_superset(buttons)
+    }
+
     /// Returns `true` if [`MouseButton::Left`] is in the set.
     ///
     /// [`MouseButton::Left`]: enum.MouseButton.html#variant.Left
@@ -259,11 +333,28 @@ pub enum Cursor {
     NotAllowed,
     ResizeLeftRight,
     ResizeUpDown,
+    /// No cursor at all, for example while a video is playing.
+    ///
+    /// Setting any other cursor shows the cursor again.
+    Hidden,
     // The platform cursor should be small. Any image data that it uses should be shared (i.e.
     // behind an `Arc` or using a platform API that does the sharing).
     // Custom(backend::window::CustomCursor),
 }
 
+/// How the cursor should be constrained to a window.
+///
+/// See [`WindowHandle::set_cursor_grab`](crate::WindowHandle::set_cursor_grab).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorGrabMode {
+    /// The cursor can move freely.
+    None,
+    /// The cursor is confined to the window area.
+    Confined,
+    /// The cursor is locked in place; only relative motion is reported.
+    Locked,
+}
+
 /// A platform-independent description of a custom cursor.
 #[derive(Clone)]
 pub struct CursorDesc {
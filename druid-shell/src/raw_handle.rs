@@ -0,0 +1,149 @@
+// Copyright 2022 The Druid Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `raw-window-handle` 0.5 support for [`WindowHandle`].
+//!
+//! winit, and so piet-wgpu, are still on 0.4, whose window handles also carry the display
+//! connection. Current `wgpu` and `ash` want the 0.5 traits, where window and display are
+//! separate, so both are derived here from the 0.4 handle winit gives us, plus the X11
+//! screen, which the 0.4 handle doesn't carry.
+
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use raw_window_handle_05 as rwh_05;
+
+use crate::window::WindowHandle;
+
+unsafe impl rwh_05::HasRawWindowHandle for WindowHandle {
+    fn raw_window_handle(&self) -> rwh_05::RawWindowHandle {
+        match HasRawWindowHandle::raw_window_handle(self) {
+            RawWindowHandle::Xlib(handle) => {
+                let mut window = rwh_05::XlibWindowHandle::empty();
+                window.window = handle.window;
+                window.visual_id = handle.visual_id;
+                rwh_05::RawWindowHandle::Xlib(window)
+            }
+            RawWindowHandle::Xcb(handle) => {
+                let mut window = rwh_05::XcbWindowHandle::empty();
+                window.window = handle.window;
+                window.visual_id = handle.visual_id;
+                rwh_05::RawWindowHandle::Xcb(window)
+            }
+            RawWindowHandle::Wayland(handle) => {
+                let mut window = rwh_05::WaylandWindowHandle::empty();
+                window.surface = handle.surface;
+                rwh_05::RawWindowHandle::Wayland(window)
+            }
+            RawWindowHandle::Win32(handle) => {
+                let mut window = rwh_05::Win32WindowHandle::empty();
+                window.hwnd = handle.hwnd;
+                window.hinstance = handle.hinstance;
+                rwh_05::RawWindowHandle::Win32(window)
+            }
+            RawWindowHandle::WinRt(handle) => {
+                let mut window = rwh_05::WinRtWindowHandle::empty();
+                window.core_window = handle.core_window;
+                rwh_05::RawWindowHandle::WinRt(window)
+            }
+            RawWindowHandle::AppKit(handle) => {
+                let mut window = rwh_05::AppKitWindowHandle::empty();
+                window.ns_window = handle.ns_window;
+                window.ns_view = handle.ns_view;
+                rwh_05::RawWindowHandle::AppKit(window)
+            }
+            RawWindowHandle::UiKit(handle) => {
+                let mut window = rwh_05::UiKitWindowHandle::empty();
+                window.ui_window = handle.ui_window;
+                window.ui_view = handle.ui_view;
+                window.ui_view_controller = handle.ui_view_controller;
+                rwh_05::RawWindowHandle::UiKit(window)
+            }
+            RawWindowHandle::Orbital(handle) => {
+                let mut window = rwh_05::OrbitalWindowHandle::empty();
+                window.window = handle.window;
+                rwh_05::RawWindowHandle::Orbital(window)
+            }
+            RawWindowHandle::AndroidNdk(handle) => {
+                let mut window = rwh_05::AndroidNdkWindowHandle::empty();
+                window.a_native_window = handle.a_native_window;
+                rwh_05::RawWindowHandle::AndroidNdk(window)
+            }
+            RawWindowHandle::Web(handle) => {
+                let mut window = rwh_05::WebWindowHandle::empty();
+                window.id = handle.id;
+                rwh_05::RawWindowHandle::Web(window)
+            }
+            RawWindowHandle::Haiku(handle) => {
+                let mut window = rwh_05::HaikuWindowHandle::empty();
+                window.b_window = handle.b_window;
+                window.b_direct_window = handle.b_direct_window;
+                rwh_05::RawWindowHandle::Haiku(window)
+            }
+            handle => {
+                tracing::error!("no raw-window-handle 0.5 equivalent for {:?}", handle);
+                // Web id 0 is reserved for "no window", which renderers reject.
+                rwh_05::RawWindowHandle::Web(rwh_05::WebWindowHandle::empty())
+            }
+        }
+    }
+}
+
+unsafe impl rwh_05::HasRawDisplayHandle for WindowHandle {
+    fn raw_display_handle(&self) -> rwh_05::RawDisplayHandle {
+        match HasRawWindowHandle::raw_window_handle(self) {
+            RawWindowHandle::Xlib(handle) => {
+                let mut display = rwh_05::XlibDisplayHandle::empty();
+                display.display = handle.display;
+                display.screen = self.x11_screen().unwrap_or_default();
+                rwh_05::RawDisplayHandle::Xlib(display)
+            }
+            RawWindowHandle::Xcb(handle) => {
+                let mut display = rwh_05::XcbDisplayHandle::empty();
+                display.connection = handle.connection;
+                display.screen = self.x11_screen().unwrap_or_default();
+                rwh_05::RawDisplayHandle::Xcb(display)
+            }
+            RawWindowHandle::Wayland(handle) => {
+                let mut display = rwh_05::WaylandDisplayHandle::empty();
+                display.display = handle.display;
+                rwh_05::RawDisplayHandle::Wayland(display)
+            }
+            RawWindowHandle::Win32(_) | RawWindowHandle::WinRt(_) => {
+                rwh_05::RawDisplayHandle::Windows(rwh_05::WindowsDisplayHandle::empty())
+            }
+            RawWindowHandle::AppKit(_) => {
+                rwh_05::RawDisplayHandle::AppKit(rwh_05::AppKitDisplayHandle::empty())
+            }
+            RawWindowHandle::UiKit(_) => {
+                rwh_05::RawDisplayHandle::UiKit(rwh_05::UiKitDisplayHandle::empty())
+            }
+            RawWindowHandle::Orbital(_) => {
+                rwh_05::RawDisplayHandle::Orbital(rwh_05::OrbitalDisplayHandle::empty())
+            }
+            RawWindowHandle::AndroidNdk(_) => {
+                rwh_05::RawDisplayHandle::Android(rwh_05::AndroidDisplayHandle::empty())
+            }
+            RawWindowHandle::Web(_) => {
+                rwh_05::RawDisplayHandle::Web(rwh_05::WebDisplayHandle::empty())
+            }
+            RawWindowHandle::Haiku(_) => {
+                rwh_05::RawDisplayHandle::Haiku(rwh_05::HaikuDisplayHandle::empty())
+            }
+            handle => {
+                tracing::error!("no raw-window-handle 0.5 equivalent for {:?}", handle);
+                // Goes with the empty window handle above.
+                rwh_05::RawDisplayHandle::Web(rwh_05::WebDisplayHandle::empty())
+            }
+        }
+    }
+}
@@ -136,6 +136,36 @@ impl Scale {
     pub fn px_to_dp_xy<T: Into<f64>>(self, x: T, y: T) -> (f64, f64) {
         (x.into() / self.x, y.into() / self.y)
     }
+
+    /// Converts a [`Point`], [`Size`], [`Rect`] or other [`Scalable`] item from display
+    /// points into pixels.
+    ///
+    /// ```
+    /// use druid_shell::kurbo::Point;
+    /// use druid_shell::Scale;
+    ///
+    /// let scale = Scale::new(2.0, 1.5);
+    /// assert_eq!(scale.to_px(Point::new(10.0, 10.0)), Point::new(20.0, 15.0));
+    /// ```
+    #[inline]
+    pub fn to_px<T: Scalable>(self, item: T) -> T {
+        item.to_px(self)
+    }
+
+    /// Converts a [`Point`], [`Size`], [`Rect`] or other [`Scalable`] item from pixels
+    /// into display points.
+    ///
+    /// ```
+    /// use druid_shell::kurbo::Size;
+    /// use druid_shell::Scale;
+    ///
+    /// let scale = Scale::new(2.0, 1.5);
+    /// assert_eq!(scale.to_dp(Size::new(20.0, 15.0)), Size::new(10.0, 10.0));
+    /// ```
+    #[inline]
+    pub fn to_dp<T: Scalable>(self, item: T) -> T {
+        item.to_dp(self)
+    }
 }
 
 impl Scalable for Vec2 {
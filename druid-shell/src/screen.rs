@@ -14,16 +14,69 @@
 
 //! Module to get information about monitors
 
-use crate::kurbo::Rect;
+use crate::kurbo::{Point, Rect, Size};
+use crate::window::WinitEvent;
+use std::cell::RefCell;
 use std::fmt;
 use std::fmt::Display;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::MonitorHandle;
+
+/// A resolution, color depth and refresh rate a [`Monitor`] supports in exclusive fullscreen.
+///
+/// Get them from [`Monitor::video_modes`] and enter them with [`Fullscreen::Exclusive`].
+///
+/// [`Fullscreen::Exclusive`]: crate::Fullscreen::Exclusive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VideoMode(pub(crate) winit::monitor::VideoMode);
+
+impl VideoMode {
+    /// Returns the resolution of the video mode, in physical pixels.
+    pub fn size(&self) -> Size {
+        let size = self.0.size();
+        Size::new(size.width.into(), size.height.into())
+    }
+
+    /// Returns the number of bits per pixel.
+    pub fn bit_depth(&self) -> u16 {
+        self.0.bit_depth()
+    }
+
+    /// Returns the refresh rate, in millihertz.
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.0.refresh_rate_millihertz()
+    }
+}
+
+impl Display for VideoMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.0.size();
+        write!(
+            f,
+            "{}x{} {}bpp @ {:.3}Hz",
+            size.width,
+            size.height,
+            self.0.bit_depth(),
+            self.0.refresh_rate_millihertz() as f64 / 1000.0
+        )
+    }
+}
+
+thread_local! {
+    /// The monitors found by the last call to [`Screen::refresh_monitors`].
+    static MONITORS: RefCell<Vec<Monitor>> = RefCell::new(Vec::new());
+}
 
 /// Monitor struct containing data about a monitor on the system
 ///
 /// Use Screen::get_monitors() to return a Vec<Monitor> of all the monitors on the system
 #[derive(Clone, Debug, PartialEq)]
 pub struct Monitor {
+    handle: Option<MonitorHandle>,
+    name: Option<String>,
     primary: bool,
+    scale_factor: f64,
+    physical_rect: Rect,
     rect: Rect,
     // TODO: Work area, cross_platform
     // https://developer.apple.com/documentation/appkit/nsscreen/1388369-visibleframe
@@ -37,24 +90,87 @@ impl Monitor {
     #[allow(dead_code)]
     pub(crate) fn new(primary: bool, rect: Rect, work_rect: Rect) -> Self {
         Monitor {
+            handle: None,
+            name: None,
             primary,
+            scale_factor: 1.0,
+            physical_rect: rect,
             rect,
             work_rect,
         }
     }
+
+    pub(crate) fn from_winit(handle: &MonitorHandle, primary: bool) -> Self {
+        let scale_factor = handle.scale_factor();
+        let position = handle.position();
+        let size = handle.size();
+        let physical_rect = Rect::from_origin_size(
+            Point::new(position.x.into(), position.y.into()),
+            Size::new(size.width.into(), size.height.into()),
+        );
+        let position = position.to_logical::<f64>(scale_factor);
+        let size = size.to_logical::<f64>(scale_factor);
+        let rect = Rect::from_origin_size(
+            Point::new(position.x, position.y),
+            Size::new(size.width, size.height),
+        );
+        Monitor {
+            handle: Some(handle.clone()),
+            name: handle.name(),
+            primary,
+            scale_factor,
+            physical_rect,
+            rect,
+            // winit doesn't tell us about the dock, menubar or taskbar.
+            work_rect: rect,
+        }
+    }
+
+    /// Returns a human-readable name of the monitor, if the platform provides one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     /// Returns true if the monitor is the primary monitor.
     /// The primary monitor has its origin at (0, 0) in virtual screen coordinates.
     pub fn is_primary(&self) -> bool {
         self.primary
     }
-    /// Returns the monitor rectangle in virtual screen coordinates.
+    /// Returns the video modes the monitor supports in exclusive fullscreen.
+    pub fn video_modes(&self) -> Vec<VideoMode> {
+        self.handle
+            .iter()
+            .flat_map(|handle| handle.video_modes())
+            .map(VideoMode)
+            .collect()
+    }
+
+    pub(crate) fn winit_handle(&self) -> Option<MonitorHandle> {
+        self.handle.clone()
+    }
+
+    /// Returns the DPI scale factor of the monitor.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns the monitor rectangle in virtual screen coordinates, in [display points].
+    ///
+    /// [display points]: crate::Scale
     pub fn virtual_rect(&self) -> Rect {
         self.rect
     }
 
+    /// Returns the monitor rectangle in virtual screen coordinates, in physical pixels.
+    pub fn physical_rect(&self) -> Rect {
+        self.physical_rect
+    }
+
     /// Returns the monitor working rectangle in virtual screen coordinates.
     /// The working rectangle excludes certain things like the dock and menubar on mac,
     /// and the taskbar on windows.
+    ///
+    /// This is currently the same as [`virtual_rect`](Monitor::virtual_rect).
     pub fn virtual_work_rect(&self) -> Rect {
         self.work_rect
     }
@@ -81,9 +197,27 @@ pub struct Screen {}
 impl Screen {
     /// Returns a vector of all the [`monitors`] on the system.
     ///
+    /// This is the list found by the last [`refresh_monitors`], which the application
+    /// does when it starts and whenever a window changes its scale.
+    ///
     /// [`monitors`]: struct.Monitor.html
+    /// [`refresh_monitors`]: Screen::refresh_monitors
     pub fn get_monitors() -> Vec<Monitor> {
-        vec![]
+        MONITORS.with(|monitors| monitors.borrow().clone())
+    }
+
+    /// Queries the available monitors from the event loop, and updates the list returned
+    /// by [`get_monitors`](Screen::get_monitors).
+    pub fn refresh_monitors(target: &EventLoopWindowTarget<WinitEvent>) {
+        let primary = target.primary_monitor();
+        let monitors = target
+            .available_monitors()
+            .map(|handle| {
+                let is_primary = primary.as_ref() == Some(&handle);
+                Monitor::from_winit(&handle, is_primary)
+            })
+            .collect();
+        MONITORS.with(|m| *m.borrow_mut() = monitors);
     }
 
     /// Returns the bounding rectangle of the total virtual screen space in pixels.
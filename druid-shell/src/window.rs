@@ -15,32 +15,42 @@
 //! Platform independent window types.
 
 use std::any::Any;
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::application::Application;
 use crate::common_util::Counter;
 use crate::dialog::{FileDialogOptions, FileInfo};
+use crate::dispatch;
 use crate::error::Error;
-use crate::keyboard::KeyEvent;
+use crate::keyboard::{KeyEvent, Modifiers};
 use crate::kurbo::{Insets, Point, Rect, Size};
 use crate::menu::Menu;
-use crate::mouse::{Cursor, CursorDesc, MouseEvent};
+use crate::mouse::{Cursor, CursorDesc, CursorGrabMode, MouseButtons, MouseEvent};
 use crate::region::Region;
 use crate::scale::Scale;
+use crate::screen::{Monitor, VideoMode};
 use crate::text::{Event, InputHandler};
 use piet_wgpu::PietText;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
-use winit::dpi::{LogicalPosition, LogicalSize};
+use winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
 use winit::event_loop::{EventLoopProxy, EventLoopWindowTarget};
 #[cfg(target_os = "macos")]
 use winit::platform::macos::WindowBuilderExtMacOS;
 use winit::window::CursorIcon;
 
 pub enum WinitEvent {
-    Idle(IdleToken),
-    Timer(winit::window::WindowId, TimerToken, std::time::Duration),
+    /// An idle callback, for the given window's handler if it came from a window's handle.
+    Idle(Option<winit::window::WindowId>, IdleToken),
+    Timer(winit::window::WindowId, TimerToken, Instant),
+    CancelTimer(winit::window::WindowId, TimerToken),
+    ContinuousRedraw(winit::window::WindowId, bool),
     NewWindow,
+    /// Sent by [`Application::quit`] to end the event loop.
+    Quit,
 }
 
 /// A token that uniquely identifies a running timer.
@@ -96,7 +106,10 @@ impl TextFieldToken {
 //NOTE: this has a From<backend::Handle> impl for construction
 /// A handle that can enqueue tasks on the window loop.
 #[derive(Clone)]
-pub struct IdleHandle(Arc<EventLoopProxy<WinitEvent>>);
+pub struct IdleHandle(
+    Arc<EventLoopProxy<WinitEvent>>,
+    Option<winit::window::WindowId>,
+);
 
 unsafe impl Sync for IdleHandle {}
 unsafe impl Send for IdleHandle {}
@@ -118,7 +131,7 @@ impl IdleHandle {
     /// be called with the `token` that was passed in.
 
     pub fn schedule_idle(&mut self, token: IdleToken) {
-        self.0.send_event(WinitEvent::Idle(token));
+        self.0.send_event(WinitEvent::Idle(self.1, token));
     }
 }
 
@@ -171,6 +184,107 @@ pub enum WindowLevel {
     DropDown,
     /// A modal dialog
     Modal,
+    /// An app window kept above normal windows, see [`WindowHandle::set_always_on_top`].
+    AlwaysOnTop,
+}
+
+impl Default for WindowLevel {
+    fn default() -> Self {
+        WindowLevel::AppWindow
+    }
+}
+
+impl WindowLevel {
+    /// winit only tells normal windows from those that stay on top.
+    fn to_winit(self) -> winit::window::WindowLevel {
+        match self {
+            WindowLevel::AppWindow => winit::window::WindowLevel::Normal,
+            WindowLevel::Tooltip
+            | WindowLevel::DropDown
+            | WindowLevel::Modal
+            | WindowLevel::AlwaysOnTop => winit::window::WindowLevel::AlwaysOnTop,
+        }
+    }
+}
+
+/// How long the last frame of a window took, see [`WindowHandle::last_frame_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub struct FrameStats {
+    /// The time spent on layout, zero if the frame needed none.
+    pub layout: Duration,
+    /// The time spent painting, including handing the frame to the GPU.
+    pub paint: Duration,
+    /// A rolling average of the layout and paint time of the recent frames.
+    pub average: Duration,
+    /// The number of frames painted so far.
+    pub frames: u64,
+}
+
+impl FrameStats {
+    /// Adds a frame to the stats.
+    fn record(&mut self, layout: Duration, paint: Duration) {
+        let total = layout + paint;
+        self.average = if self.frames == 0 {
+            total
+        } else {
+            // Each frame weighs a sixteenth, so a slow frame shows for a couple of dozen more.
+            (self.average * 15 + total) / 16
+        };
+        self.layout = layout;
+        self.paint = paint;
+        self.frames += 1;
+    }
+}
+
+/// Converts a window area from winit into a rect in display points.
+///
+/// The position is taken to be the origin if the platform can't tell it.
+fn logical_rect(
+    position: Option<PhysicalPosition<i32>>,
+    size: PhysicalSize<u32>,
+    scale: f64,
+) -> Rect {
+    let origin = position.map_or(Point::ZERO, |p| {
+        let p = p.to_logical::<f64>(scale);
+        Point::new(p.x, p.y)
+    });
+    let size = size.to_logical::<f64>(scale);
+    Size::new(size.width, size.height)
+        .to_rect()
+        .with_origin(origin)
+}
+
+/// Returns `true` if `size` is the result of a resize to `requested`.
+///
+/// Converting display points to pixels and back can be off by one, so that is allowed for.
+fn is_requested_size(requested: PhysicalSize<u32>, size: PhysicalSize<u32>) -> bool {
+    requested.width.abs_diff(size.width) <= 1 && requested.height.abs_diff(size.height) <= 1
+}
+
+/// The area of a window to repaint, collected between paints.
+#[derive(Debug, Default)]
+struct Damage {
+    region: Region,
+    /// Set once a redraw was requested, until the paint takes the region.
+    redraw_requested: bool,
+}
+
+impl Damage {
+    /// Adds `rect` to the damage, returning `true` if a redraw has to be requested for it.
+    fn add(&mut self, rect: Rect) -> bool {
+        // The same area tends to be invalidated over and over before the paint.
+        if !self.region.rects().iter().any(|r| r.union(rect) == *r) {
+            self.region.add_rect(rect);
+        }
+        !mem::replace(&mut self.redraw_requested, true)
+    }
+
+    /// Takes the damaged region for a paint.
+    fn take(&mut self) -> Region {
+        self.redraw_requested = false;
+        mem::take(&mut self.region)
+    }
 }
 
 /// Contains the different states a Window can be in.
@@ -179,21 +293,311 @@ pub enum WindowState {
     Maximized,
     Minimized,
     Restored,
+    /// Borderless fullscreen on the monitor the window is currently on.
+    Fullscreen,
+}
+
+/// The color scheme of the system, or of a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    fn to_winit(self) -> winit::window::Theme {
+        match self {
+            Theme::Light => winit::window::Theme::Light,
+            Theme::Dark => winit::window::Theme::Dark,
+        }
+    }
+}
+
+impl From<winit::window::Theme> for Theme {
+    fn from(theme: winit::window::Theme) -> Theme {
+        match theme {
+            winit::window::Theme::Light => Theme::Light,
+            winit::window::Theme::Dark => Theme::Dark,
+        }
+    }
+}
+
+/// The kinds of fullscreen a window can enter with [`WindowHandle::set_fullscreen`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fullscreen {
+    /// Change the monitor's video mode and take it over exclusively.
+    Exclusive(VideoMode),
+    /// Cover the given monitor with a borderless window, or the one the window
+    /// is currently on if `None`.
+    Borderless(Option<Monitor>),
+}
+
+impl Fullscreen {
+    fn to_winit(&self) -> winit::window::Fullscreen {
+        match self {
+            Fullscreen::Exclusive(mode) => winit::window::Fullscreen::Exclusive(mode.0.clone()),
+            Fullscreen::Borderless(monitor) => winit::window::Fullscreen::Borderless(
+                monitor.as_ref().and_then(Monitor::winit_handle),
+            ),
+        }
+    }
+}
+
+/// How urgently a window asks for the user's attention, see
+/// [`WindowHandle::request_user_attention`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrgencyLevel {
+    /// Bounce the dock icon once on macOS, or flash the taskbar button until the
+    /// window is focused on Windows.
+    Informational,
+    /// Bounce the dock icon until the application is focused on macOS, or flash both
+    /// the window and the taskbar button on Windows.
+    Critical,
+}
+
+/// The edge or corner of a window that is being resized with
+/// [`WindowHandle::drag_resize_window`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeDirection {
+    East,
+    North,
+    NorthEast,
+    NorthWest,
+    South,
+    SouthEast,
+    SouthWest,
+    West,
+}
+
+impl ResizeDirection {
+    /// Returns the direction to resize in when the mouse is pressed at `pos`, if it is
+    /// within `border` display points of the edges of a window of the given `size`.
+    ///
+    /// This is meant for the resize grips of a window without decorations, whose
+    /// [`WinHandler::mouse_down`] can pass the result to
+    /// [`WindowHandle::drag_resize_window`].
+    pub fn from_edge(pos: Point, size: Size, border: f64) -> Option<ResizeDirection> {
+        let west = pos.x < border;
+        let east = pos.x >= size.width - border;
+        let north = pos.y < border;
+        let south = pos.y >= size.height - border;
+        match (north, south, west, east) {
+            (true, _, true, _) => Some(ResizeDirection::NorthWest),
+            (true, _, _, true) => Some(ResizeDirection::NorthEast),
+            (_, true, true, _) => Some(ResizeDirection::SouthWest),
+            (_, true, _, true) => Some(ResizeDirection::SouthEast),
+            (true, _, _, _) => Some(ResizeDirection::North),
+            (_, true, _, _) => Some(ResizeDirection::South),
+            (_, _, true, _) => Some(ResizeDirection::West),
+            (_, _, _, true) => Some(ResizeDirection::East),
+            _ => None,
+        }
+    }
+}
+
+/// An image used as the window icon, e.g. in the title bar or the taskbar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Icon {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl Icon {
+    /// Create an icon from 32bpp RGBA pixel data, in row-major order.
+    ///
+    /// Returns an error if the length of `rgba` doesn't match `width` and `height`.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Icon, Error> {
+        let expected = width as usize * height as usize * 4;
+        if rgba.len() != expected {
+            return Err(anyhow::anyhow!(
+                "icon data is {} bytes, expected {} for a {}x{} image",
+                rgba.len(),
+                expected,
+                width,
+                height
+            )
+            .into());
+        }
+        Ok(Icon {
+            rgba,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the width of the icon in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the icon in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn to_winit(&self) -> Option<winit::window::Icon> {
+        winit::window::Icon::from_rgba(self.rgba.clone(), self.width, self.height)
+            .map_err(|e| tracing::warn!("invalid window icon: {}", e))
+            .ok()
+    }
 }
 
 /// A handle to a platform window object.
 #[derive(Clone)]
-pub struct WindowHandle(Arc<winit::window::Window>, Arc<EventLoopProxy<WinitEvent>>);
+pub struct WindowHandle(
+    Arc<winit::window::Window>,
+    Arc<EventLoopProxy<WinitEvent>>,
+    Arc<Mutex<HandleState>>,
+);
+
+/// State shared by all the clones of a [`WindowHandle`].
+///
+/// A [`WindowBuilder`] collects the initial state, which the built handle then starts with.
+#[derive(Default)]
+struct HandleState {
+    /// The outer position and inner size of the window before it entered fullscreen,
+    /// to go back to when it leaves.
+    windowed: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    /// The last known position of the mouse, in display points.
+    cursor_position: Option<Point>,
+    /// The modifier keys held down when we last heard about them.
+    mods: Modifiers,
+    /// The mouse buttons held down as of the last mouse event.
+    buttons: MouseButtons,
+    menu: Option<Menu>,
+    /// The title without the subtitle, once either was set through the handle.
+    title: Option<String>,
+    subtitle: Option<String>,
+    represented_file: Option<PathBuf>,
+    level: WindowLevel,
+    /// Set while the window is disabled with [`WindowHandle::set_enabled`].
+    disabled: bool,
+    /// The text fields registered with [`WindowHandle::add_text_field`].
+    text_fields: Vec<TextFieldToken>,
+    focused_text_field: Option<TextFieldToken>,
+    /// Set when the caret of the focused text field moved, or another field got focus, so
+    /// the IME cursor area needs to follow it.
+    text_field_moved: bool,
+    /// The limits of the drawing area, in display points.
+    min_size: Option<Size>,
+    max_size: Option<Size>,
+    /// The area invalidated since the last paint, in display points.
+    invalid: Damage,
+    /// Set when the focused text field was changed with [`WindowHandle::focus_text_field`]
+    /// or [`WindowHandle::blur_text_field`], for the handler to follow.
+    text_focus_requested: bool,
+    /// Set while the cursor is hidden with [`Cursor::Hidden`].
+    cursor_hidden: bool,
+    /// The text last put in the title bar through the handle.
+    shown_title: Option<String>,
+    frame_stats: FrameStats,
+    /// Set with [`WindowHandle::set_ime_allowed`], to keep the IME on without a text field.
+    ime_allowed: bool,
+    /// The size, in pixels, last asked for with [`WindowHandle::set_size`] or
+    /// [`WindowHandle::set_outer_size`], until the platform resizes the window.
+    requested_size: Option<PhysicalSize<u32>>,
+    /// Whether the last resize was to the requested size.
+    resize_requested: bool,
+    /// Timers cancelled with [`WindowHandle::cancel_timer`] that the run loop may not
+    /// have heard about yet.
+    cancelled_timers: HashSet<TimerToken>,
+    /// Set while the window is hidden, by [`WindowHandle::hide`] or by building it hidden.
+    hidden: bool,
+}
 
 impl WindowHandle {
+    /// Returns winit's id for this window, which identifies it in winit events.
     pub fn id(&self) -> winit::window::WindowId {
         self.0.id()
     }
     /// Make this window visible.
     ///
-    /// This is part of the initialization process; it should only be called
-    /// once, when a window is first created.
-    pub fn show(&self) {}
+    /// This is part of the initialization process, but it can also show a window
+    /// again after [`hide`], or one built with [`WindowBuilder::set_visible`] as hidden.
+    ///
+    /// [`hide`]: WindowHandle::hide
+    pub fn show(&self) {
+        self.2.lock().unwrap().hidden = false;
+        self.0.set_visible(true);
+    }
+
+    /// Returns `false` if the window was hidden with [`hide`] or built hidden, and hasn't
+    /// been shown since.
+    ///
+    /// [`hide`]: WindowHandle::hide
+    pub fn is_visible(&self) -> bool {
+        !self.2.lock().unwrap().hidden
+    }
+
+    /// Hide this window, without closing it.
+    ///
+    /// A hidden window has no taskbar button either, and keeps all of its state. To send a
+    /// window to the tray when it's minimized, hide it on [`WindowState::Minimized`] and
+    /// [`show`] it again later, along with a [`set_window_state`] to restore it.
+    ///
+    /// [`show`]: WindowHandle::show
+    /// [`set_window_state`]: WindowHandle::set_window_state
+    pub fn hide(&self) {
+        self.2.lock().unwrap().hidden = true;
+        self.0.set_visible(false);
+    }
+
+    /// Enable or disable input to the window, e.g. while a modal dialog is open on top of it.
+    ///
+    /// On Windows the system disables the window. Elsewhere it stays interactive as far as
+    /// the platform is concerned, and it is up to the application to ignore its input while
+    /// [`is_enabled`] is `false`; druid does this for its windows.
+    ///
+    /// [`is_enabled`]: WindowHandle::is_enabled
+    pub fn set_enabled(&self, enabled: bool) {
+        self.2.lock().unwrap().disabled = !enabled;
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowExtWindows;
+            self.0.set_enable(enabled);
+        }
+    }
+
+    /// Returns `false` if the window was disabled with [`set_enabled`].
+    ///
+    /// [`set_enabled`]: WindowHandle::set_enabled
+    pub fn is_enabled(&self) -> bool {
+        !self.2.lock().unwrap().disabled
+    }
+
+    /// Set whether the window is left out of the taskbar, while staying visible.
+    ///
+    /// This is only supported on Windows, and does nothing elsewhere.
+    #[allow(unused_variables)]
+    pub fn set_skip_taskbar(&self, skip: bool) {
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowExtWindows;
+            self.0.set_skip_taskbar(skip);
+        }
+    }
+
+    /// Set the opacity of the whole window, from `0.0` for invisible to `1.0` for opaque.
+    ///
+    /// Unlike a [transparent] window, this fades everything in the window evenly, e.g. to
+    /// fade a notification out before closing it. Values outside `0.0..=1.0` are clamped.
+    ///
+    /// This is only supported on Windows for now. Elsewhere it does nothing. Returns `false`
+    /// if the opacity couldn't be set.
+    ///
+    /// [transparent]: WindowBuilder::set_transparent
+    #[allow(unused_variables)]
+    pub fn set_opacity(&self, opacity: f64) -> bool {
+        let opacity = opacity.max(0.0).min(1.0);
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowExtWindows;
+            layered::set_alpha(self.0.hwnd() as isize, (opacity * 255.0).round() as u8)
+        }
+        #[cfg(not(target_os = "windows"))]
+        false
+    }
 
     /// Close the window.
     pub fn close(&self) {}
@@ -209,14 +613,48 @@ impl WindowHandle {
             WindowState::Maximized => self.0.set_maximized(true),
             WindowState::Minimized => self.0.set_minimized(true),
             WindowState::Restored => {
+                self.set_fullscreen(None);
                 self.0.set_maximized(false);
                 self.0.set_minimized(false);
             }
+            WindowState::Fullscreen => self.set_fullscreen(Some(Fullscreen::Borderless(None))),
+        }
+    }
+
+    /// Enters the given kind of fullscreen, or goes back to a normal window with `None`.
+    ///
+    /// Leaving fullscreen restores the position and size the window had before
+    /// entering it.
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let mut state = self.2.lock().unwrap();
+        match fullscreen {
+            Some(fullscreen) => {
+                if self.0.fullscreen().is_none() {
+                    let position = self.0.outer_position().unwrap_or_default();
+                    state.windowed = Some((position, self.0.inner_size()));
+                }
+                self.0.set_fullscreen(Some(fullscreen.to_winit()));
+            }
+            None => {
+                if self.0.fullscreen().is_some() {
+                    self.0.set_fullscreen(None);
+                }
+                if let Some((position, size)) = state.windowed.take() {
+                    self.0.set_outer_position(position);
+                    self.0.set_inner_size(size);
+                }
+            }
         }
     }
 
     /// Gets the state of the window.
     pub fn get_window_state(&self) -> WindowState {
+        if self.0.fullscreen().is_some() {
+            return WindowState::Fullscreen;
+        }
+        if self.0.is_minimized() == Some(true) {
+            return WindowState::Minimized;
+        }
         let maximized = self.0.is_maximized();
         if maximized {
             WindowState::Maximized
@@ -230,11 +668,70 @@ impl WindowHandle {
     /// because this refers to the current location of the mouse, you should probably call this
     /// function in response to every relevant [`WinHandler::mouse_move`].
     ///
-    /// This is currently only implemented on Windows.
-    pub fn handle_titlebar(&self, val: bool) {}
+    /// Client-side dragging only starts while a mouse button is held down, so passing `true`
+    /// from a [`WinHandler::mouse_down`] in the custom titlebar region moves the window until
+    /// the button is released. At other times this does nothing.
+    pub fn handle_titlebar(&self, val: bool) {
+        if val {
+            if let Err(e) = self.0.drag_window() {
+                tracing::debug!("failed to start dragging the window: {}", e);
+            }
+        }
+    }
+
+    /// Starts resizing the window from the given edge or corner, following the mouse until the
+    /// button is released. Like [`handle_titlebar`], this should be called in response to a
+    /// [`WinHandler::mouse_down`], e.g. on the border of a window without decorations.
+    ///
+    /// [`handle_titlebar`]: WindowHandle::handle_titlebar
+    pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), Error> {
+        let direction = match direction {
+            ResizeDirection::East => winit::window::ResizeDirection::East,
+            ResizeDirection::North => winit::window::ResizeDirection::North,
+            ResizeDirection::NorthEast => winit::window::ResizeDirection::NorthEast,
+            ResizeDirection::NorthWest => winit::window::ResizeDirection::NorthWest,
+            ResizeDirection::South => winit::window::ResizeDirection::South,
+            ResizeDirection::SouthEast => winit::window::ResizeDirection::SouthEast,
+            ResizeDirection::SouthWest => winit::window::ResizeDirection::SouthWest,
+            ResizeDirection::West => winit::window::ResizeDirection::West,
+        };
+        self.0
+            .drag_resize_window(direction)
+            .map_err(|e| Error::Other(Arc::new(anyhow::anyhow!("{}", e))))
+    }
 
     /// Set whether the window should show titlebar.
-    pub fn show_titlebar(&self, show_titlebar: bool) {}
+    ///
+    /// On macOS, winit cannot toggle the transparent, full-size titlebar used by
+    /// [`WindowBuilder::show_titlebar`] after the window has been created, so hiding the
+    /// titlebar at runtime removes the window decorations altogether.
+    pub fn show_titlebar(&self, show_titlebar: bool) {
+        self.0.set_decorations(show_titlebar);
+    }
+
+    /// Returns the theme of the window, which follows the system's unless it was set with
+    /// [`set_theme`](WindowHandle::set_theme). Returns `None` if the platform doesn't say.
+    pub fn theme(&self) -> Option<Theme> {
+        self.0.theme().map(Theme::from)
+    }
+
+    /// Forces the window to use a light or dark theme, or to follow the system again with
+    /// `None`.
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        self.0.set_theme(theme.map(Theme::to_winit));
+    }
+
+    /// Requests the user's attention, e.g. by bouncing the dock icon or flashing the taskbar
+    /// button, until the window is focused. Passing `None` cancels a previous request.
+    ///
+    /// Nothing happens if the window already has focus.
+    pub fn request_user_attention(&self, level: Option<UrgencyLevel>) {
+        let level = level.map(|level| match level {
+            UrgencyLevel::Informational => winit::window::UserAttentionType::Informational,
+            UrgencyLevel::Critical => winit::window::UserAttentionType::Critical,
+        });
+        self.0.request_user_attention(level);
+    }
 
     /// Sets the position of the window in [display points](crate::Scale), relative to the origin of the
     /// virtual screen.
@@ -249,10 +746,11 @@ impl WindowHandle {
     ///
     /// [display points]: crate::Scale
     pub fn get_position(&self) -> Point {
-        let point = self
-            .0
-            .outer_position()
-            .map(|p| Point::new(p.x.into(), p.y.into()));
+        let scale = self.0.scale_factor();
+        let point = self.0.outer_position().map(|p| {
+            let p = p.to_logical::<f64>(scale);
+            Point::new(p.x, p.y)
+        });
         point.unwrap_or(Point::ZERO)
     }
 
@@ -270,25 +768,9 @@ impl WindowHandle {
     ///
     /// [display points]: crate::Scale
     pub fn content_insets(&self) -> Insets {
-        let outer_size = self.0.outer_size();
-        let outer_position = self
-            .0
-            .outer_position()
-            .map(|p| Point::new(p.x.into(), p.y.into()))
-            .unwrap_or(Point::ZERO);
-        let outer_rect = Size::new(outer_size.width.into(), outer_size.height.into())
-            .to_rect()
-            .with_origin(outer_position);
-
-        let inner_size = self.0.inner_size();
-        let inner_position = self
-            .0
-            .inner_position()
-            .map(|p| Point::new(p.x.into(), p.y.into()))
-            .unwrap_or(Point::ZERO);
-        let inner_rect = Size::new(inner_size.width.into(), outer_size.height.into())
-            .to_rect()
-            .with_origin(inner_position);
+        let scale = self.0.scale_factor();
+        let outer_rect = logical_rect(self.0.outer_position().ok(), self.0.outer_size(), scale);
+        let inner_rect = logical_rect(self.0.inner_position().ok(), self.0.inner_size(), scale);
         outer_rect - inner_rect
     }
 
@@ -304,8 +786,131 @@ impl WindowHandle {
     /// [display points]: crate::Scale
     pub fn set_size(&self, size: impl Into<Size>) {
         let size: Size = size.into();
+        self.request_inner_size(LogicalSize::new(size.width, size.height));
+    }
+
+    /// Set the window's outer size in [display points], including the title bar and any other
+    /// decorations the window system draws around the drawing area.
+    ///
+    /// Use this, and not [`set_size`], to restore a size measured with [`get_outer_size`].
+    /// There is no platform call for this, so the size of the decorations is taken from
+    /// their current size and the drawing area is resized to fit.
+    ///
+    /// [`set_size`]: WindowHandle::set_size
+    /// [`get_outer_size`]: WindowHandle::get_outer_size
+    /// [display points]: crate::Scale
+    pub fn set_outer_size(&self, size: impl Into<Size>) {
+        let size: Size = size.into();
+        let scale = self.0.scale_factor();
+        let outer = self.0.outer_size().to_logical::<f64>(scale);
+        let inner = self.0.inner_size().to_logical::<f64>(scale);
+        let width = size.width - (outer.width - inner.width);
+        let height = size.height - (outer.height - inner.height);
+        self.request_inner_size(LogicalSize::new(width.max(0.0), height.max(0.0)));
+    }
+
+    fn request_inner_size(&self, size: LogicalSize<f64>) {
+        let physical = size.to_physical(self.0.scale_factor());
+        self.2.lock().unwrap().requested_size = Some(physical);
+        self.0.set_inner_size(size);
+    }
+
+    /// Whether the last change of the window's size was asked for with [`set_size`] or
+    /// [`set_outer_size`], rather than made by the user.
+    ///
+    /// Check this while handling the size change, e.g. to only save sizes the user chose.
+    ///
+    /// [`set_size`]: WindowHandle::set_size
+    /// [`set_outer_size`]: WindowHandle::set_outer_size
+    pub fn resize_was_requested(&self) -> bool {
+        self.2.lock().unwrap().resize_requested
+    }
+
+    /// Tells the handle the window was resized to `size`, in pixels, for
+    /// [`resize_was_requested`].
+    ///
+    /// Only the first resize after a request can be the requested one. Platforms may round
+    /// the size, so it matches if it is within a pixel.
+    ///
+    /// [`resize_was_requested`]: WindowHandle::resize_was_requested
+    #[doc(hidden)]
+    pub fn record_resize(&self, size: PhysicalSize<u32>) {
+        let mut state = self.2.lock().unwrap();
+        state.resize_requested = state
+            .requested_size
+            .take()
+            .map_or(false, |requested| is_requested_size(requested, size));
+    }
+
+    /// Set the window's minimum drawing area size in [display points].
+    ///
+    /// The actual minimum window size in pixels will depend on the platform DPI settings.
+    ///
+    /// This should be considered a request to the platform to set the minimum size of the window.
+    /// The platform might increase the size a tiny bit due to DPI.
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_min_size(&self, size: impl Into<Size>) {
+        let size: Size = size.into();
+        self.2.lock().unwrap().min_size = Some(size);
         self.0
-            .set_inner_size(LogicalSize::new(size.width, size.height));
+            .set_min_inner_size(Some(LogicalSize::new(size.width, size.height)));
+    }
+
+    /// Set the window's maximum drawing area size in [display points].
+    ///
+    /// Passing a size with an infinite width or height removes the limit again.
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_max_size(&self, size: impl Into<Size>) {
+        let size: Size = size.into();
+        if size.width.is_finite() && size.height.is_finite() {
+            self.2.lock().unwrap().max_size = Some(size);
+            self.0
+                .set_max_inner_size(Some(LogicalSize::new(size.width, size.height)));
+        } else {
+            self.2.lock().unwrap().max_size = None;
+            self.0.set_max_inner_size(None::<LogicalSize<f64>>);
+        }
+    }
+
+    /// Set or remove both limits of the window's drawing area at once, in [display points].
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_min_max(&self, min: Option<Size>, max: Option<Size>) {
+        let mut state = self.2.lock().unwrap();
+        state.min_size = min;
+        state.max_size = max;
+        drop(state);
+        self.0
+            .set_min_inner_size(min.map(|size| LogicalSize::new(size.width, size.height)));
+        self.0
+            .set_max_inner_size(max.map(|size| LogicalSize::new(size.width, size.height)));
+    }
+
+    /// Returns the minimum size of the drawing area, if one was set, in [display points].
+    ///
+    /// [display points]: crate::Scale
+    pub fn get_min_size(&self) -> Option<Size> {
+        self.2.lock().unwrap().min_size
+    }
+
+    /// Returns the maximum size of the drawing area, if one was set, in [display points].
+    ///
+    /// [display points]: crate::Scale
+    pub fn get_max_size(&self) -> Option<Size> {
+        self.2.lock().unwrap().max_size
+    }
+
+    /// Make interactive resizing snap the window's drawing area to multiples of the given
+    /// size in [display points], or remove that constraint with `None`.
+    ///
+    /// This is not supported on every platform, notably not on Windows.
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        let increments = increments.map(|size| LogicalSize::new(size.width, size.height));
+        self.0.set_resize_increments(increments);
     }
 
     /// Gets the window size, in [display points].
@@ -316,20 +921,121 @@ impl WindowHandle {
         Size::new(inner_size.width.into(), inner_size.height.into())
     }
 
+    /// Gets the window's outer size in [display points], including the title bar and any other
+    /// decorations the window system draws around the drawing area.
+    ///
+    /// This is the size to save when persisting a window's geometry; [`get_size`] and
+    /// [`set_size`] only cover the drawing area. See also [`content_insets`].
+    ///
+    /// [`get_size`]: WindowHandle::get_size
+    /// [`set_size`]: WindowHandle::set_size
+    /// [`content_insets`]: WindowHandle::content_insets
+    /// [display points]: crate::Scale
+    pub fn get_outer_size(&self) -> Size {
+        let outer_size = self.0.outer_size().to_logical::<f64>(self.0.scale_factor());
+        Size::new(outer_size.width, outer_size.height)
+    }
+
     /// Sets the [`WindowLevel`](crate::WindowLevel), the z-order in the Window system / compositor
     ///
-    /// We do not currently have a getter method, mostly because the system's levels aren't a
-    /// perfect one-to-one map to `druid_shell`'s levels. A getter method may be added in the
-    /// future.
-    pub fn set_level(&self, level: WindowLevel) {}
+    /// The system's levels aren't a perfect one-to-one map to `druid_shell`'s levels: every
+    /// level but [`WindowLevel::AppWindow`] keeps the window above normal windows.
+    pub fn set_level(&self, level: WindowLevel) {
+        self.2.lock().unwrap().level = level;
+        self.0.set_window_level(level.to_winit());
+    }
+
+    /// Returns the [`WindowLevel`] last set with [`set_level`], or the one the window was
+    /// built with.
+    ///
+    /// This is what was asked for, which the platform may map to a coarser level.
+    ///
+    /// [`set_level`]: WindowHandle::set_level
+    pub fn get_level(&self) -> WindowLevel {
+        self.2.lock().unwrap().level
+    }
+
+    /// Asks the compositor to blur whatever is behind the window.
+    ///
+    /// This only shows through where the window is [transparent], and is just so much
+    /// glass on top of an opaque background.
+    ///
+    /// Returns `false` if the platform can't blur behind windows, in which case this does
+    /// nothing. Only Wayland compositors that implement the blur protocol support it for
+    /// now; macOS vibrancy and Windows acrylic aren't available through `winit`.
+    ///
+    /// [transparent]: WindowBuilder::set_transparent
+    pub fn set_blur(&self, blur: bool) -> bool {
+        if !self.supports_blur() {
+            return false;
+        }
+        self.0.set_blur(blur);
+        true
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ))]
+    fn supports_blur(&self) -> bool {
+        use winit::platform::wayland::WindowExtWayland;
+        self.0.wayland_surface().is_some()
+    }
+
+    #[cfg(not(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    )))]
+    fn supports_blur(&self) -> bool {
+        false
+    }
+
+    /// The X11 screen the window is on, if it is an X11 window.
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    ))]
+    pub(crate) fn x11_screen(&self) -> Option<i32> {
+        use winit::platform::x11::WindowExtX11;
+        self.0.xlib_screen_id()
+    }
+
+    #[cfg(not(all(
+        unix,
+        not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+    )))]
+    pub(crate) fn x11_screen(&self) -> Option<i32> {
+        None
+    }
+
+    /// Keep the window above all normal windows, or let it be covered by them again.
+    ///
+    /// This sets the level to [`WindowLevel::AlwaysOnTop`] or [`WindowLevel::AppWindow`],
+    /// as [`set_level`] does.
+    ///
+    /// [`set_level`]: WindowHandle::set_level
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        let level = if always_on_top {
+            WindowLevel::AlwaysOnTop
+        } else {
+            WindowLevel::AppWindow
+        };
+        self.set_level(level);
+    }
 
     /// Bring this window to the front of the window stack and give it focus.
-    pub fn bring_to_front_and_focus(&self) {}
+    pub fn bring_to_front_and_focus(&self) {
+        self.0.set_minimized(false);
+        self.0.focus_window();
+    }
 
     /// Request that [`prepare_paint`] and [`paint`] be called next time there's the opportunity to
     /// render another frame. This differs from [`invalidate`] and [`invalidate_rect`] in that it
     /// doesn't invalidate any part of the window.
     ///
+    /// The frame is requested with winit's `request_redraw`, and painted when the platform
+    /// delivers `RedrawRequested`, usually in step with the display's refresh.
+    ///
     /// [`invalidate`]: WindowHandle::invalidate
     /// [`invalidate_rect`]: WindowHandle::invalidate_rect
     /// [`paint`]: WinHandler::paint
@@ -338,23 +1044,140 @@ impl WindowHandle {
         self.0.request_redraw();
     }
 
+    /// Keep redrawing the window as fast as possible, even when nothing requests an
+    /// animation frame, until this is called again with `false`.
+    ///
+    /// While any window redraws continuously the run loop polls for events instead of
+    /// waiting for them, so this should only be enabled while something is animating.
+    pub fn set_continuous_redraw(&self, enabled: bool) {
+        self.1
+            .send_event(WinitEvent::ContinuousRedraw(self.id(), enabled));
+    }
+
     /// Request invalidation of the entire window contents.
     pub fn invalidate(&self) {
-        self.0.request_redraw();
+        let size = self.0.inner_size().to_logical::<f64>(self.0.scale_factor());
+        self.invalidate_rect(Size::new(size.width, size.height).to_rect());
     }
 
     /// Request invalidation of a region of the window.
+    ///
+    /// The rectangles of all invalidations before the next paint are collected, and the
+    /// handler gets their union in [`paint`]. However many there are, the window is
+    /// painted once for all of them.
+    ///
+    /// [`paint`]: WinHandler::paint
     pub fn invalidate_rect(&self, rect: Rect) {
-        self.0.request_redraw();
+        let request_redraw = self.2.lock().unwrap().invalid.add(rect);
+        if request_redraw {
+            self.0.request_redraw();
+        }
+    }
+
+    /// Returns the region invalidated since the last call, in display points, and starts
+    /// collecting a new one.
+    ///
+    /// Code that paints a window itself should call it when painting, so that the next
+    /// invalidation requests a new redraw.
+    pub fn take_invalid_region(&self) -> Region {
+        self.2.lock().unwrap().invalid.take()
     }
 
     /// Set the title for this menu.
     pub fn set_title(&self, title: &str) {
-        self.0.set_title(title)
+        let mut state = self.2.lock().unwrap();
+        state.title = Some(title.to_string());
+        self.update_title(&mut state);
+    }
+
+    /// Returns the title of the window, without the subtitle.
+    pub fn title(&self) -> String {
+        match &self.2.lock().unwrap().title {
+            Some(title) => title.clone(),
+            None => self.0.title(),
+        }
+    }
+
+    /// Set the title of this window, along with a subtitle such as the name of the open
+    /// document, or no subtitle with `None`.
+    ///
+    /// No platform has a separate place for the subtitle through winit, so for now it is
+    /// shown after the title, as in "Title — Subtitle".
+    pub fn set_title_and_subtitle(&self, title: &str, subtitle: Option<&str>) {
+        let mut state = self.2.lock().unwrap();
+        state.title = Some(title.to_string());
+        state.subtitle = subtitle.map(str::to_string);
+        self.update_title(&mut state);
     }
 
-    /// Set the top-level menu for this window.
-    pub fn set_menu(&self, menu: Menu) {}
+    /// Set the file this window is showing, or `None` if it shows no file.
+    ///
+    /// The file name is shown after the title as long as no subtitle is set. macOS would
+    /// show a proxy icon for the file, but winit doesn't support that yet.
+    pub fn set_represented_file(&self, path: Option<PathBuf>) {
+        let mut state = self.2.lock().unwrap();
+        if state.title.is_none() {
+            state.title = Some(self.0.title());
+        }
+        state.represented_file = path;
+        self.update_title(&mut state);
+    }
+
+    /// Returns the file set with [`set_represented_file`].
+    ///
+    /// [`set_represented_file`]: WindowHandle::set_represented_file
+    pub fn represented_file(&self) -> Option<PathBuf> {
+        self.2.lock().unwrap().represented_file.clone()
+    }
+
+    /// Shows the title, subtitle and file name in the title bar, unless they are shown
+    /// already. Apps often set the same title on every update pass.
+    fn update_title(&self, state: &mut HandleState) {
+        let title = state.title.as_deref().unwrap_or_default();
+        let file_name = state
+            .represented_file
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy());
+        let shown = match state.subtitle.as_deref().or(file_name.as_deref()) {
+            Some(subtitle) if !title.is_empty() => format!("{} — {}", title, subtitle),
+            Some(subtitle) => subtitle.to_string(),
+            None => title.to_string(),
+        };
+        if state.shown_title.as_ref() != Some(&shown) {
+            self.0.set_title(&shown);
+            state.shown_title = Some(shown);
+        }
+    }
+
+    /// Set the icon for this window, or remove it with `None`.
+    pub fn set_window_icon(&self, icon: Option<Icon>) {
+        self.0
+            .set_window_icon(icon.as_ref().and_then(Icon::to_winit));
+    }
+
+    /// Set the top-level menu for this window, replacing the previous one.
+    ///
+    /// No menu bar is shown: winit has no native menus, and none are built here. Only the
+    /// [`HotKey`]s of the menu's enabled items work, see [`menu_command_for_key`]. For a
+    /// window with a handler, pressing one calls [`WinHandler::command`] instead of
+    /// [`WinHandler::key_down`].
+    ///
+    /// [`HotKey`]: crate::HotKey
+    /// [`menu_command_for_key`]: WindowHandle::menu_command_for_key
+    pub fn set_menu(&self, menu: Menu) {
+        self.2.lock().unwrap().menu = Some(menu);
+    }
+
+    /// Returns the `id` of the item in this window's menu whose [`HotKey`] matches the
+    /// event, if any. Such key presses should be handled as if the item was selected,
+    /// by calling [`WinHandler::command`], instead of being treated as text input.
+    ///
+    /// [`HotKey`]: crate::HotKey
+    pub fn menu_command_for_key(&self, event: &KeyEvent) -> Option<u32> {
+        let state = self.2.lock().unwrap();
+        state.menu.as_ref()?.command_for_key(event)
+    }
 
     /// Get access to a type that can perform text layout.
     // pub fn text(&self) -> PietText {
@@ -371,20 +1194,128 @@ impl WindowHandle {
     ///
     /// Returns the `TextFieldToken` associated with this new text input.
     pub fn add_text_field(&self) -> TextFieldToken {
-        TextFieldToken(0)
+        let token = TextFieldToken::next();
+        self.2.lock().unwrap().text_fields.push(token);
+        token
     }
 
     /// Unregister a previously registered text input receiver.
     ///
     /// If `token` is the text field currently focused, the platform automatically
     /// sets the focused field to `None`.
-    pub fn remove_text_field(&self, token: TextFieldToken) {}
+    pub fn remove_text_field(&self, token: TextFieldToken) {
+        let mut state = self.2.lock().unwrap();
+        state.text_fields.retain(|field| *field != token);
+        if state.focused_text_field == Some(token) {
+            state.focused_text_field = None;
+            let allowed = state.ime_allowed;
+            drop(state);
+            self.0.set_ime_allowed(allowed);
+        }
+    }
+
+    /// Keep the IME enabled even while no text field has focus.
+    ///
+    /// This is for windows that draw the composition themselves without registering a
+    /// text field, following winit's `Ime::Preedit` events instead.
+    ///
+    /// While the IME is enabled, the platform leaves drawing the text being composed to
+    /// the application; only the candidate window is drawn by the IME itself.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        let mut state = self.2.lock().unwrap();
+        state.ime_allowed = allowed;
+        let allowed = allowed || state.focused_text_field.is_some();
+        drop(state);
+        self.0.set_ime_allowed(allowed);
+    }
 
     /// Notify the platform that the focused text input receiver has changed.
     ///
     /// This must be called any time focus changes to a different text input, or
     /// when focus switches away from a text input.
-    pub fn set_focused_text_field(&self, active_field: Option<TextFieldToken>) {}
+    ///
+    /// The IME is only enabled while a text field has focus, and its cursor area then
+    /// follows that field, as after an [`update_text_field`].
+    ///
+    /// [`update_text_field`]: WindowHandle::update_text_field
+    pub fn set_focused_text_field(&self, active_field: Option<TextFieldToken>) {
+        let mut state = self.2.lock().unwrap();
+        let active_field = active_field.filter(|token| {
+            let registered = state.text_fields.contains(token);
+            if !registered {
+                tracing::warn!("{:?} is not a text field of this window", token);
+            }
+            registered
+        });
+        if state.focused_text_field == active_field {
+            return;
+        }
+        state.focused_text_field = active_field;
+        state.text_field_moved = active_field.is_some();
+        let allowed = active_field.is_some() || state.ime_allowed;
+        drop(state);
+        self.0.set_ime_allowed(allowed);
+    }
+
+    /// Give a text field focus, e.g. to point the user at a field that needs fixing.
+    ///
+    /// Unlike [`set_focused_text_field`], which tells the platform about a focus change
+    /// that already happened, this asks for one: the window's handler is told with
+    /// [`WinHandler::text_field_focus_changed`], so that it can focus the widget the field
+    /// belongs to. In druid, that widget gets focus.
+    ///
+    /// [`set_focused_text_field`]: WindowHandle::set_focused_text_field
+    pub fn focus_text_field(&self, token: TextFieldToken) {
+        self.set_focused_text_field(Some(token));
+        self.2.lock().unwrap().text_focus_requested = true;
+    }
+
+    /// Take the focus away from the focused text field, if any, and disable the IME.
+    ///
+    /// The handler is told as for [`focus_text_field`].
+    ///
+    /// [`focus_text_field`]: WindowHandle::focus_text_field
+    pub fn blur_text_field(&self) {
+        self.set_focused_text_field(None);
+        self.2.lock().unwrap().text_focus_requested = true;
+    }
+
+    /// Returns the newly focused text field if [`focus_text_field`] or [`blur_text_field`]
+    /// was called since the last call.
+    ///
+    /// [`focus_text_field`]: WindowHandle::focus_text_field
+    /// [`blur_text_field`]: WindowHandle::blur_text_field
+    #[doc(hidden)]
+    pub fn take_text_focus_request(&self) -> Option<Option<TextFieldToken>> {
+        let mut state = self.2.lock().unwrap();
+        if std::mem::take(&mut state.text_focus_requested) {
+            Some(state.focused_text_field)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the text field that has focus, as set with [`set_focused_text_field`].
+    ///
+    /// [`set_focused_text_field`]: WindowHandle::set_focused_text_field
+    pub fn focused_text_field(&self) -> Option<TextFieldToken> {
+        self.2.lock().unwrap().focused_text_field
+    }
+
+    /// Tell the platform where the text being composed is, in [display points]
+    /// relative to the window's content area.
+    ///
+    /// The platform uses this to place IME candidate windows next to the caret,
+    /// so it should be updated whenever the caret of the focused text field moves.
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_ime_cursor_area(&self, position: Point, size: Size) {
+        let area = self.scale().to_px(Rect::from_origin_size(position, size));
+        self.0.set_ime_cursor_area(
+            PhysicalPosition::new(area.x0, area.y0),
+            PhysicalSize::new(area.width(), area.height()),
+        );
+    }
 
     /// Notify the platform that some text input state has changed, such as the
     /// selection, contents, etc.
@@ -392,7 +1323,56 @@ impl WindowHandle {
     /// This method should *never* be called in response to edits from a
     /// `InputHandler`; only in response to changes from the application:
     /// scrolling, remote edits, etc.
-    pub fn update_text_field(&self, token: TextFieldToken, update: Event) {}
+    ///
+    /// Only updates to the focused text field matter to the platform. A [`Reset`] drops
+    /// any composition that is in progress. Other updates mean the IME cursor area has to
+    /// move with the caret: for a window built with [`WindowBuilder::set_handler`] this is
+    /// done once the handler returns, using its [`acquire_input_lock`].
+    ///
+    /// [`Reset`]: Event::Reset
+    /// [`acquire_input_lock`]: WinHandler::acquire_input_lock
+    pub fn update_text_field(&self, token: TextFieldToken, update: Event) {
+        let mut state = self.2.lock().unwrap();
+        if state.focused_text_field != Some(token) {
+            return;
+        }
+        state.text_field_moved = true;
+        drop(state);
+        if update == Event::Reset {
+            // Turning the IME off and on again is the only way winit gives us to throw
+            // away the composition.
+            self.0.set_ime_allowed(false);
+            self.0.set_ime_allowed(true);
+        }
+    }
+
+    /// Returns the focused text field if [`update_text_field`] was called for it since
+    /// the last call, meaning its IME cursor area should be updated.
+    ///
+    /// [`update_text_field`]: WindowHandle::update_text_field
+    #[doc(hidden)]
+    pub fn take_moved_text_field(&self) -> Option<TextFieldToken> {
+        let mut state = self.2.lock().unwrap();
+        if std::mem::take(&mut state.text_field_moved) {
+            state.focused_text_field
+        } else {
+            None
+        }
+    }
+
+    /// Returns how long the last frame took to lay out and paint, for profiling.
+    pub fn last_frame_stats(&self) -> FrameStats {
+        self.2.lock().unwrap().frame_stats
+    }
+
+    /// Adds the timing of a frame to [`last_frame_stats`], for the [`WinHandler`] to call
+    /// after painting.
+    ///
+    /// [`last_frame_stats`]: WindowHandle::last_frame_stats
+    #[doc(hidden)]
+    pub fn record_frame(&self, layout: Duration, paint: Duration) {
+        self.2.lock().unwrap().frame_stats.record(layout, paint);
+    }
 
     /// Schedule a timer.
     ///
@@ -405,15 +1385,106 @@ impl WindowHandle {
     /// like blinking a cursor or triggering tooltips, not for anything
     /// requiring precision.
     pub fn request_timer(&self, deadline: Duration) -> TimerToken {
+        self.request_timer_at(Instant::now() + deadline)
+    }
+
+    /// Schedule a timer for a point in time, like [`request_timer`] does for a duration.
+    ///
+    /// Use this to keep a series of timers from drifting, e.g. by aligning each tick of a
+    /// clock with the time the previous one was meant to fire at instead of the time it
+    /// was handled. A deadline in the past fires as soon as possible.
+    ///
+    /// [`request_timer`]: WindowHandle::request_timer
+    pub fn request_timer_at(&self, deadline: Instant) -> TimerToken {
         let token = TimerToken::next();
         self.1
             .send_event(WinitEvent::Timer(self.id(), token, deadline));
         token
     }
 
+    /// Cancel a timer previously scheduled with [`request_timer`].
+    ///
+    /// [`WinHandler::timer`] is not called for `token` after this returns, even if the
+    /// timer is already due. Cancelling a timer that has already fired, or that belongs
+    /// to another window, does nothing.
+    ///
+    /// [`request_timer`]: WindowHandle::request_timer
+    pub fn cancel_timer(&self, token: TimerToken) {
+        self.2.lock().unwrap().cancelled_timers.insert(token);
+        self.1.send_event(WinitEvent::CancelTimer(self.id(), token));
+    }
+
+    /// Returns `true` if `token` was cancelled with [`cancel_timer`], and forgets about it.
+    ///
+    /// The run loop checks this before firing a timer, as the timer may be due before the
+    /// cancellation reaches it.
+    ///
+    /// [`cancel_timer`]: WindowHandle::cancel_timer
+    #[doc(hidden)]
+    pub fn take_cancelled_timer(&self, token: TimerToken) -> bool {
+        self.2.lock().unwrap().cancelled_timers.remove(&token)
+    }
+
+    /// Returns the last known position of the mouse in the window, in [display points],
+    /// or `None` if the mouse isn't over the window.
+    ///
+    /// [display points]: crate::Scale
+    pub fn cursor_position(&self) -> Option<Point> {
+        self.2.lock().unwrap().cursor_position
+    }
+
+    /// Records the position the mouse was last seen at, for [`cursor_position`].
+    ///
+    /// [`cursor_position`]: WindowHandle::cursor_position
+    #[doc(hidden)]
+    pub fn record_cursor_position(&self, position: Option<Point>) {
+        self.2.lock().unwrap().cursor_position = position;
+    }
+
+    /// Returns the modifier keys that are currently held down, as far as this window knows.
+    ///
+    /// Unlike the modifiers of input events, this can be checked at any time, e.g. from a
+    /// timer. It only changes while the window has focus.
+    pub fn current_modifiers(&self) -> Modifiers {
+        self.2.lock().unwrap().mods
+    }
+
+    /// Records the modifier keys that are held down, for [`current_modifiers`].
+    ///
+    /// [`current_modifiers`]: WindowHandle::current_modifiers
+    #[doc(hidden)]
+    pub fn record_modifiers(&self, mods: Modifiers) {
+        self.2.lock().unwrap().mods = mods;
+    }
+
+    /// Returns the mouse buttons that are currently held down, as of the last mouse event
+    /// in this window.
+    ///
+    /// This lets a key handler tell whether a button is held, e.g. to start panning when
+    /// space is pressed during a drag.
+    pub fn mouse_buttons(&self) -> MouseButtons {
+        self.2.lock().unwrap().buttons
+    }
+
+    /// Records the mouse buttons that are held down, for [`mouse_buttons`].
+    ///
+    /// [`mouse_buttons`]: WindowHandle::mouse_buttons
+    #[doc(hidden)]
+    pub fn record_mouse_buttons(&self, buttons: MouseButtons) {
+        self.2.lock().unwrap().buttons = buttons;
+    }
+
     /// Set the cursor icon.
+    ///
+    /// [`Cursor::Hidden`] hides the cursor until another cursor is set.
     pub fn set_cursor(&mut self, cursor: &Cursor) {
+        let hidden = *cursor == Cursor::Hidden;
+        let was_hidden = std::mem::replace(&mut self.2.lock().unwrap().cursor_hidden, hidden);
+        if hidden != was_hidden {
+            self.0.set_cursor_visible(!hidden);
+        }
         let cursor = match cursor {
+            Cursor::Hidden => return,
             Cursor::Arrow => CursorIcon::Arrow,
             Cursor::IBeam => CursorIcon::Text,
             Cursor::Pointer => CursorIcon::Hand,
@@ -425,6 +1496,41 @@ impl WindowHandle {
         self.0.set_cursor_icon(cursor);
     }
 
+    /// Set whether the cursor is visible while it is over this window.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.0.set_cursor_visible(visible);
+    }
+
+    /// Confine or lock the cursor to this window.
+    ///
+    /// Not every platform supports every [`CursorGrabMode`]; for example, locking
+    /// is unavailable on some platforms. An error is returned in that case.
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), Error> {
+        let mode = match mode {
+            CursorGrabMode::None => winit::window::CursorGrabMode::None,
+            CursorGrabMode::Confined => winit::window::CursorGrabMode::Confined,
+            CursorGrabMode::Locked => winit::window::CursorGrabMode::Locked,
+        };
+        self.0
+            .set_cursor_grab(mode)
+            .map_err(|e| Error::Other(Arc::new(anyhow::anyhow!("{}", e))))
+    }
+
+    /// Set whether the window receives mouse input, or lets it pass through to whatever
+    /// is beneath it.
+    ///
+    /// With `false`, a [transparent] overlay becomes click-through. The window gets no mouse
+    /// events at all then, so it has to turn hit testing back on by other means, such as a
+    /// hotkey, to take input again. Not every platform supports this; an error is returned
+    /// in that case.
+    ///
+    /// [transparent]: WindowBuilder::set_transparent
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), Error> {
+        self.0
+            .set_cursor_hittest(hittest)
+            .map_err(|e| Error::Other(Arc::new(anyhow::anyhow!("{}", e))))
+    }
+
     pub fn make_cursor(&self, desc: &CursorDesc) -> Option<Cursor> {
         None
     }
@@ -454,7 +1560,16 @@ impl WindowHandle {
 
     /// Get a handle that can be used to schedule an idle task.
     pub fn get_idle_handle(&self) -> Option<IdleHandle> {
-        Some(IdleHandle(self.1.clone()))
+        Some(IdleHandle(self.1.clone(), Some(self.0.id())))
+    }
+
+    /// Returns the monitor the window is currently on, if it can be determined.
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        let primary = self.0.primary_monitor();
+        self.0.current_monitor().map(|handle| {
+            let is_primary = primary.as_ref() == Some(&handle);
+            Monitor::from_winit(&handle, is_primary)
+        })
     }
 
     /// Get the DPI scale of the window.
@@ -462,21 +1577,78 @@ impl WindowHandle {
     /// The returned [`Scale`](crate::Scale) is a copy and thus its information will be stale after
     /// the platform DPI changes. This means you should not stash it and rely on it later; it is
     /// only guaranteed to be valid for the current pass of the runloop.
+    pub fn scale(&self) -> Scale {
+        let factor = self.0.scale_factor();
+        Scale::new(factor, factor)
+    }
+
+    /// Get the DPI scale factor of the window, the same for both axes.
+    ///
+    /// This is the raw factor behind [`scale`](WindowHandle::scale), and just as likely to
+    /// become stale.
     pub fn get_scale(&self) -> f64 {
         self.0.scale_factor()
     }
 }
 
+// The raw-window-handle 0.5 traits, including `HasRawDisplayHandle`, are in `raw_handle`.
 unsafe impl HasRawWindowHandle for WindowHandle {
     fn raw_window_handle(&self) -> RawWindowHandle {
         self.0.raw_window_handle()
     }
 }
 
+/// The part of the Win32 API behind [`WindowHandle::set_opacity`], which winit doesn't wrap.
+#[cfg(target_os = "windows")]
+mod layered {
+    use windows_sys::Win32::Foundation::{GetLastError, SetLastError, HWND};
+    #[cfg(target_pointer_width = "64")]
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowLongPtrW, SetWindowLongPtrW};
+    #[cfg(target_pointer_width = "32")]
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongW as GetWindowLongPtrW, SetWindowLongW as SetWindowLongPtrW,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SetLayeredWindowAttributes, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+    };
+
+    /// What the window long functions take and return.
+    #[cfg(target_pointer_width = "64")]
+    type LongPtr = isize;
+    #[cfg(target_pointer_width = "32")]
+    type LongPtr = i32;
+
+    /// Makes the window a layered window, if it isn't one yet, and sets its alpha.
+    ///
+    /// Returns `false` if Windows refused either.
+    pub(super) fn set_alpha(hwnd: HWND, alpha: u8) -> bool {
+        unsafe {
+            // Both window long functions can legitimately return 0, so failure is only
+            // told apart by the last error.
+            SetLastError(0);
+            let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            if style == 0 && GetLastError() != 0 {
+                return false;
+            }
+            let layered = WS_EX_LAYERED as LongPtr;
+            if style & layered == 0 {
+                SetLastError(0);
+                let previous = SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | layered);
+                if previous == 0 && GetLastError() != 0 {
+                    return false;
+                }
+            }
+            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA) != 0
+        }
+    }
+}
+
 /// A builder type for creating new windows.
 pub struct WindowBuilder(
     winit::window::WindowBuilder,
     Arc<EventLoopProxy<WinitEvent>>,
+    HandleState,
+    Option<Box<dyn WinHandler>>,
 );
 
 impl WindowBuilder {
@@ -485,13 +1657,24 @@ impl WindowBuilder {
     /// Takes the [`Application`](crate::Application) that this window is for.
     pub fn new(app: Application) -> WindowBuilder {
         let event_proxy = app.state.borrow().event_proxy.clone();
-        WindowBuilder(winit::window::WindowBuilder::new(), event_proxy)
+        WindowBuilder(
+            winit::window::WindowBuilder::new(),
+            event_proxy,
+            Default::default(),
+            None,
+        )
     }
 
     /// Set the [`WinHandler`] for this window.
     ///
-    /// This is the object that will receive callbacks from this window.
-    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {}
+    /// This is the object that will receive callbacks from this window. It is
+    /// [connected](WinHandler::connect) when the window is built, and then gets the window's
+    /// events once they are passed to [`Application::handle_event`].
+    ///
+    /// [`Application::handle_event`]: crate::Application::handle_event
+    pub fn set_handler(&mut self, handler: Box<dyn WinHandler>) {
+        self.3 = Some(handler);
+    }
 
     /// Set the window's initial drawing area size in [display points].
     ///
@@ -519,12 +1702,44 @@ impl WindowBuilder {
     ///
     /// [display points]: crate::Scale
     pub fn set_min_size(mut self, size: Size) -> Self {
+        self.2.min_size = Some(size);
         self.0 = self
             .0
             .with_min_inner_size(LogicalSize::new(size.width, size.height));
         self
     }
 
+    /// Set the window's maximum drawing area size in [display points].
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_max_size(mut self, size: Size) -> Self {
+        self.2.max_size = Some(size);
+        self.0 = self
+            .0
+            .with_max_inner_size(LogicalSize::new(size.width, size.height));
+        self
+    }
+
+    /// Make interactive resizing snap the window's drawing area to multiples of the given
+    /// size in [display points].
+    ///
+    /// [display points]: crate::Scale
+    pub fn set_resize_increments(mut self, increments: Size) -> Self {
+        self.0 = self
+            .0
+            .with_resize_increments(LogicalSize::new(increments.width, increments.height));
+        self
+    }
+
+    /// Set whether the window is visible once it is built.
+    ///
+    /// A window built hidden can be shown later with [`WindowHandle::show`].
+    pub fn set_visible(mut self, visible: bool) -> Self {
+        self.2.hidden = !visible;
+        self.0 = self.0.with_visible(visible);
+        self
+    }
+
     /// Set whether the window should be resizable.
     pub fn resizable(mut self, resizable: bool) -> Self {
         self.0 = self.0.with_resizable(resizable);
@@ -550,7 +1765,13 @@ impl WindowBuilder {
     }
 
     /// Set whether the window background should be transparent
-    pub fn set_transparent(&mut self, transparent: bool) {}
+    ///
+    /// This asks the platform for a window with an alpha channel, which
+    /// [`WindowHandle::set_blur`] also needs. What shows through depends on the renderer's
+    /// surface supporting alpha.
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.0 = mem::take(&mut self.0).with_transparent(transparent);
+    }
 
     /// Sets the initial window position in [display points], relative to the origin of the
     /// virtual screen.
@@ -563,8 +1784,57 @@ impl WindowBuilder {
         self
     }
 
+    /// Set the application id that the desktop uses to group the window and match it with
+    /// its `.desktop` entry.
+    ///
+    /// This is the app id on Wayland and the `WM_CLASS` on X11, and should usually be the
+    /// name of the `.desktop` file without the extension. Other platforms ignore it.
+    #[allow(unused_mut, unused_variables)]
+    pub fn set_app_id(mut self, app_id: impl Into<String>) -> Self {
+        #[cfg(all(
+            unix,
+            not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+        ))]
+        {
+            use winit::platform::wayland::WindowBuilderExtWayland;
+            use winit::platform::x11::WindowBuilderExtX11;
+            let app_id = app_id.into();
+            let builder = mem::take(&mut self.0);
+            let builder = WindowBuilderExtX11::with_name(builder, app_id.clone(), app_id.clone());
+            self.0 = WindowBuilderExtWayland::with_name(builder, app_id.clone(), app_id);
+        }
+        self
+    }
+
+    /// Make the window owned by `owner`, so that it stays in front of it, and is minimized
+    /// and closed along with it.
+    ///
+    /// This is only supported on Windows, and does nothing elsewhere.
+    #[allow(unused_mut, unused_variables)]
+    pub fn set_owner(mut self, owner: &WindowHandle) -> Self {
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::WindowBuilderExtWindows;
+            if let RawWindowHandle::Win32(handle) = owner.raw_window_handle() {
+                self.0 = self.0.with_owner_window(handle.hwnd as _);
+            }
+        }
+        self
+    }
+
+    /// Set whether the compositor should blur what is behind the window.
+    ///
+    /// This is ignored where blurring isn't supported, see [`WindowHandle::set_blur`].
+    pub fn set_blur(mut self, blur: bool) -> Self {
+        self.0 = self.0.with_blur(blur);
+        self
+    }
+
     /// Sets the initial [`WindowLevel`].
-    pub fn set_level(&mut self, level: WindowLevel) {}
+    pub fn set_level(&mut self, level: WindowLevel) {
+        self.0 = mem::take(&mut self.0).with_window_level(level.to_winit());
+        self.2.level = level;
+    }
 
     /// Set the window's initial title.
     pub fn set_title(mut self, title: impl Into<String>) -> Self {
@@ -572,8 +1842,19 @@ impl WindowBuilder {
         self
     }
 
+    /// Set the window's initial icon.
+    pub fn set_window_icon(mut self, icon: Icon) -> Self {
+        self.0 = self.0.with_window_icon(icon.to_winit());
+        self
+    }
+
     /// Set the window's menu.
-    pub fn set_menu(&mut self, menu: Menu) {}
+    ///
+    /// Like [`WindowHandle::set_menu`], this only sets up the menu's hotkeys; no menu bar
+    /// is shown.
+    pub fn set_menu(&mut self, menu: Menu) {
+        self.2.menu = Some(menu);
+    }
 
     /// Sets the initial state of the window.
     pub fn set_window_state(mut self, state: WindowState) -> Self {
@@ -581,10 +1862,27 @@ impl WindowBuilder {
             WindowState::Maximized => self.0 = self.0.with_maximized(true),
             WindowState::Minimized => (),
             WindowState::Restored => (),
+            WindowState::Fullscreen => {
+                self.0 = self
+                    .0
+                    .with_fullscreen(Some(winit::window::Fullscreen::Borderless(None)))
+            }
         }
         self
     }
 
+    /// Forces the window to use a light or dark theme, instead of the system's.
+    pub fn set_theme(mut self, theme: Theme) -> Self {
+        self.0 = self.0.with_theme(Some(theme.to_winit()));
+        self
+    }
+
+    /// Sets the kind of fullscreen the window should start in.
+    pub fn set_fullscreen(mut self, fullscreen: Fullscreen) -> Self {
+        self.0 = self.0.with_fullscreen(Some(fullscreen.to_winit()));
+        self
+    }
+
     /// Attempt to construct the platform window.
     ///
     /// If this fails, your application should exit.
@@ -593,10 +1891,17 @@ impl WindowBuilder {
         window_target: &EventLoopWindowTarget<T>,
     ) -> Result<WindowHandle, Error> {
         let event_proxy = self.1.clone();
-        self.0
+        let state = self.2;
+        let handle = self
+            .0
             .build(window_target)
-            .map(|w| WindowHandle(Arc::new(w), event_proxy))
-            .map_err(|e| Error::Other(std::sync::Arc::new(anyhow::anyhow!("{}", e))))
+            .map(|w| WindowHandle(Arc::new(w), event_proxy, Arc::new(Mutex::new(state))))
+            .map_err(|e| Error::Other(std::sync::Arc::new(anyhow::anyhow!("{}", e))))?;
+        if let Some(mut handler) = self.3 {
+            handler.connect(&handle);
+            dispatch::register(&handle, handler);
+        }
+        Ok(handle)
     }
 }
 
@@ -632,9 +1937,10 @@ pub trait WinHandler {
     /// [`WindowHandle::invalidate`].
     fn prepare_paint(&mut self);
 
-    /// Request the handler to paint the window contents.  `invalid` is the region in [display
-    /// points](crate::Scale) that needs to be repainted; painting outside the invalid region will
-    /// have no effect.
+    /// Request the handler to paint the window contents.
+    ///
+    /// The surface doesn't keep the previous frame, so the whole window has to be painted
+    /// every time.
     fn paint(&mut self);
 
     /// Called when the resources need to be rebuilt.
@@ -646,6 +1952,11 @@ pub trait WinHandler {
     fn rebuild_resources(&mut self) {}
 
     /// Called when a menu item is selected.
+    ///
+    /// No menus are shown, so for now this is only called when a [`HotKey`] of the
+    /// window's menu is pressed, see [`WindowHandle::set_menu`].
+    ///
+    /// [`HotKey`]: crate::HotKey
     #[allow(unused_variables)]
     fn command(&mut self, id: u32) {}
 
@@ -705,6 +2016,14 @@ pub trait WinHandler {
         panic!("release_input_lock was called on a WinHandler that did not expect text input.")
     }
 
+    /// Called when the application focused a text field with
+    /// [`WindowHandle::focus_text_field`], or took the focus away from one with
+    /// [`WindowHandle::blur_text_field`].
+    ///
+    /// The handler should give `token`'s widget focus, or no widget for `None`.
+    #[allow(unused_variables)]
+    fn text_field_focus_changed(&mut self, token: Option<TextFieldToken>) {}
+
     /// Called on a mouse wheel event.
     ///
     /// The polarity is the amount to be added to the scroll position,
@@ -773,3 +2092,68 @@ pub trait WinHandler {
     /// Get a reference to the handler state. Used mostly by idle handlers.
     fn as_any(&mut self) -> &mut dyn Any;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidations_before_a_paint_redraw_once() {
+        let mut damage = Damage::default();
+        let caret = Rect::new(10.0, 10.0, 12.0, 30.0);
+        let redraws = (0..10).filter(|_| damage.add(caret)).count();
+        assert_eq!(redraws, 1);
+        assert_eq!(damage.take().rects(), &[caret]);
+
+        // The next invalidation after the paint needs a new one.
+        assert!(damage.add(caret));
+        assert!(!damage.add(Rect::new(0.0, 0.0, 5.0, 5.0)));
+        assert_eq!(damage.take().rects().len(), 2);
+    }
+
+    #[test]
+    fn content_insets_of_a_decorated_window() {
+        let scale = 2.0;
+        let outer = logical_rect(
+            Some(PhysicalPosition::new(100, 100)),
+            PhysicalSize::new(804, 664),
+            scale,
+        );
+        // A 30 point title bar, and 1 point borders on the other sides.
+        let inner = logical_rect(
+            Some(PhysicalPosition::new(102, 160)),
+            PhysicalSize::new(800, 602),
+            scale,
+        );
+        assert_eq!(outer - inner, Insets::new(1.0, 30.0, 1.0, 1.0));
+
+        // Without decorations both areas are the same.
+        let inner = outer;
+        assert_eq!(outer - inner, Insets::ZERO);
+    }
+
+    #[test]
+    fn resizes_are_recognized_up_to_rounding() {
+        let requested = PhysicalSize::new(801, 600);
+        assert!(is_requested_size(requested, PhysicalSize::new(801, 600)));
+        assert!(is_requested_size(requested, PhysicalSize::new(800, 601)));
+        assert!(!is_requested_size(requested, PhysicalSize::new(799, 600)));
+        assert!(!is_requested_size(requested, PhysicalSize::new(801, 640)));
+    }
+
+    #[test]
+    fn frame_stats_average_recent_frames() {
+        let ms = Duration::from_millis;
+        let mut stats = FrameStats::default();
+        stats.record(ms(4), ms(12));
+        assert_eq!(stats.average, ms(16));
+        assert_eq!(stats.frames, 1);
+
+        // A slow frame only counts for a sixteenth.
+        stats.record(ms(0), ms(32));
+        assert_eq!(stats.average, ms(17));
+        assert_eq!(stats.layout, ms(0));
+        assert_eq!(stats.paint, ms(32));
+        assert_eq!(stats.frames, 2);
+    }
+}
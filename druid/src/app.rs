@@ -14,22 +14,30 @@
 
 //! Window building and app lifecycle.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::ext_event::{ExtEventHost, ExtEventSink};
 use crate::kurbo::{Point, Size};
 use crate::menu::MenuManager;
-use crate::shell::{Application, Error as PlatformError, WindowBuilder, WindowHandle, WindowLevel};
+use crate::piet::Color;
+use crate::shell::{
+    Application, Error as PlatformError, Fullscreen, Icon, Monitor, Scale, Screen, Theme,
+    WindowBuilder, WindowHandle, WindowLevel,
+};
 use crate::widget::LabelText;
-use crate::win_handler::{AppHandler, AppState};
+use crate::win_handler::{AppHandler, AppState, EXT_EVENT_IDLE_TOKEN};
 use crate::window::WindowId;
-use crate::{AppDelegate, Data, Env, Event, LocalizedString, Menu, MouseEvent, Widget};
+use crate::{
+    AppDelegate, Data, Env, Event, FileDropEvent, InternalEvent, LocalizedString, Menu, MouseEvent,
+    Widget,
+};
 
 use druid_shell::kurbo::Vec2;
 use druid_shell::{
-    winit_key, KbKey, KeyEvent, KeyState, Modifiers, MouseButton, MouseButtons, TimerToken,
-    WindowState, WinitEvent,
+    wheel_delta, winit_key_with_text, winit_mods_with_text, winit_native_code, KbKey, KeyEvent,
+    KeyState, Modifiers, MouseButton, MouseButtons, TimerToken, WindowState, WinitEvent,
 };
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
 
@@ -43,6 +51,33 @@ pub struct AppLauncher<T> {
     l10n_resources: Option<(Vec<String>, String)>,
     delegate: Option<Box<dyn AppDelegate<T>>>,
     ext_event_host: ExtEventHost,
+    ext_event_batch: Duration,
+    event_loop_mode: EventLoopMode,
+    scroll_speed: f64,
+    coalesce_mouse_moves: bool,
+}
+
+/// How the event loop waits for new events.
+///
+/// See [`AppLauncher::with_event_loop_mode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventLoopMode {
+    /// Sleep until the next platform event or timer. This is the default on native
+    /// platforms.
+    Wait,
+    /// Keep going around the event loop without waiting, so that animations and timers
+    /// keep up with the browser's frames. This is the default on the web.
+    Poll,
+}
+
+impl Default for EventLoopMode {
+    fn default() -> Self {
+        if cfg!(target_arch = "wasm32") {
+            EventLoopMode::Poll
+        } else {
+            EventLoopMode::Wait
+        }
+    }
 }
 
 /// Defines how a windows size should be determined
@@ -64,6 +99,7 @@ pub struct WindowConfig {
     pub(crate) size_policy: WindowSizePolicy,
     pub(crate) size: Option<Size>,
     pub(crate) min_size: Option<Size>,
+    pub(crate) max_size: Option<Size>,
     pub(crate) position: Option<Point>,
     pub(crate) resizable: Option<bool>,
     pub(crate) transparent: Option<bool>,
@@ -71,6 +107,20 @@ pub struct WindowConfig {
     pub(crate) set_title: Option<String>,
     pub(crate) level: Option<WindowLevel>,
     pub(crate) state: Option<WindowState>,
+    pub(crate) icon: Option<Icon>,
+    pub(crate) resize_increments: Option<Size>,
+    pub(crate) aspect_ratio: Option<f64>,
+    pub(crate) theme: Option<Theme>,
+    pub(crate) visible: Option<bool>,
+    pub(crate) background: Option<Color>,
+    pub(crate) blur: Option<bool>,
+    pub(crate) scale_factor: Option<f64>,
+    pub(crate) physical_pixels: Option<bool>,
+    pub(crate) app_id: Option<String>,
+    pub(crate) fullscreen_monitor: Option<usize>,
+    pub(crate) zoom_sensitivity: Option<f64>,
+    pub(crate) owner: Option<WindowId>,
+    pub(crate) suppress_key_repeat: Option<bool>,
 }
 
 /// A description of a window to be instantiated.
@@ -93,7 +143,13 @@ pub struct PendingWindow<T> {
     pub(crate) transparent: bool,
     pub(crate) menu: Option<MenuManager<T>>,
     pub(crate) size_policy: WindowSizePolicy, // This is copied over from the WindowConfig
-                                              // when the native window is constructed.
+    // when the native window is constructed.
+    pub(crate) aspect_ratio: Option<f64>, // Also copied over from the WindowConfig.
+    pub(crate) background: Option<Color>, // This one too.
+    pub(crate) scale_override: Option<f64>, // And this one.
+    pub(crate) zoom_sensitivity: f64,
+    pub(crate) owner: Option<WindowId>,
+    pub(crate) suppress_key_repeat: bool,
 }
 
 impl<T: Data> PendingWindow<T> {
@@ -109,6 +165,12 @@ impl<T: Data> PendingWindow<T> {
             menu: MenuManager::platform_default(),
             transparent: false,
             size_policy: WindowSizePolicy::User,
+            aspect_ratio: None,
+            background: None,
+            scale_override: None,
+            zoom_sensitivity: 1.0,
+            owner: None,
+            suppress_key_repeat: false,
         }
     }
 
@@ -134,6 +196,9 @@ impl<T: Data> PendingWindow<T> {
     /// `menu` is a callback for creating the menu. Its first argument is the id of the window that
     /// will have the menu, or `None` if it's creating the root application menu for an app with no
     /// menus (which can happen, for example, on macOS).
+    ///
+    /// The menu isn't shown as a menu bar, since there are no native menus yet; only the
+    /// hotkeys of its items send their commands.
     pub fn menu(
         mut self,
         menu: impl FnMut(Option<WindowId>, &T, &Env) -> Menu<T> + 'static,
@@ -151,6 +216,10 @@ impl<T: Data> AppLauncher<T> {
             l10n_resources: None,
             delegate: None,
             ext_event_host: ExtEventHost::new(),
+            ext_event_batch: Duration::from_millis(8),
+            event_loop_mode: EventLoopMode::default(),
+            scroll_speed: 1.0,
+            coalesce_mouse_moves: true,
         }
     }
 
@@ -197,20 +266,35 @@ impl<T: Data> AppLauncher<T> {
     ///
     /// # Panics
     ///
-    /// Panics if the subscriber fails to initialize.
+    /// Panics if the subscriber fails to initialize, for example because a global subscriber
+    /// has already been set. See [`try_log_to_console`] for a version that doesn't panic.
+    ///
+    /// [`try_log_to_console`]: AppLauncher::try_log_to_console
     pub fn log_to_console(self) -> Self {
+        self.try_log_to_console()
+            .expect("failed to initialize the console logger");
+        self
+    }
+
+    /// Initialize the subscriber of [`log_to_console`], unless the application already set
+    /// a global subscriber of its own.
+    ///
+    /// Returns an error, and leaves the existing subscriber in place, if there is one.
+    ///
+    /// [`log_to_console`]: AppLauncher::log_to_console
+    pub fn try_log_to_console(&self) -> Result<(), tracing::subscriber::SetGlobalDefaultError> {
+        use tracing_subscriber::prelude::*;
         #[cfg(not(target_arch = "wasm32"))]
         {
-            use tracing_subscriber::prelude::*;
             let filter_layer = tracing_subscriber::filter::LevelFilter::DEBUG;
             let fmt_layer = tracing_subscriber::fmt::layer()
                 // Display target (eg "my_crate::some_mod::submod") with logs
                 .with_target(true);
 
-            tracing_subscriber::registry()
+            let subscriber = tracing_subscriber::registry()
                 .with(filter_layer)
-                .with(fmt_layer)
-                .init();
+                .with(fmt_layer);
+            tracing::subscriber::set_global_default(subscriber)
         }
         // Note - tracing-wasm might not work in headless Node.js. Probably doesn't matter anyway,
         // because this is a GUI framework, so wasm targets will virtually always be browsers.
@@ -220,9 +304,10 @@ impl<T: Data> AppLauncher<T> {
             let config = tracing_wasm::WASMLayerConfigBuilder::new()
                 .set_max_level(tracing::Level::DEBUG)
                 .build();
-            tracing_wasm::set_as_global_default_with_config(config)
+            let subscriber =
+                tracing_subscriber::registry().with(tracing_wasm::WASMLayer::new(config));
+            tracing::subscriber::set_global_default(subscriber)
         }
-        self
     }
 
     /// Use custom localization resource
@@ -238,6 +323,51 @@ impl<T: Data> AppLauncher<T> {
         self
     }
 
+    /// Set how long to wait between handling commands from [`ExtEventSink`]s.
+    ///
+    /// Commands that arrive within this interval of the last batch are held back and
+    /// handled together in the next one, with a single update of the widgets. This keeps
+    /// a worker thread flooding the application with commands from using up the CPU.
+    /// The default is 8 milliseconds; `Duration::ZERO` handles commands as they arrive.
+    ///
+    /// [`ExtEventSink`]: struct.ExtEventSink.html
+    pub fn batch_external_events(mut self, interval: Duration) -> Self {
+        self.ext_event_batch = interval;
+        self
+    }
+
+    /// Set how the event loop waits for new events.
+    ///
+    /// The default depends on the target: [`EventLoopMode::Poll`] on `wasm32`, and
+    /// [`EventLoopMode::Wait`] everywhere else.
+    pub fn with_event_loop_mode(mut self, mode: EventLoopMode) -> Self {
+        self.event_loop_mode = mode;
+        self
+    }
+
+    /// Scale the distance scrolled by mouse wheels and trackpads.
+    ///
+    /// [`Event::Wheel`] deltas are multiplied by `speed`, so a value below `1.0` slows
+    /// scrolling down. The default is `1.0`, which scrolls [`theme::SCROLL_LINE_HEIGHT`]
+    /// display points per line, and one display point per point of trackpad movement.
+    ///
+    /// [`theme::SCROLL_LINE_HEIGHT`]: crate::theme::SCROLL_LINE_HEIGHT
+    pub fn with_scroll_speed(mut self, speed: f64) -> Self {
+        self.scroll_speed = speed;
+        self
+    }
+
+    /// Set whether mouse moves between two turns of the event loop are merged into one.
+    ///
+    /// A mouse polling at 1000Hz can move many times per frame, and by default only the
+    /// last of these moves is sent as an [`Event::MouseMove`], right before the next mouse
+    /// or keyboard input. Turn this off if you want every sample, e.g. for freehand
+    /// drawing.
+    pub fn coalesce_mouse_moves(mut self, coalesce: bool) -> Self {
+        self.coalesce_mouse_moves = coalesce;
+        self
+    }
+
     /// Returns an [`ExtEventSink`] that can be moved between threads,
     /// and can be used to submit commands back to the application.
     ///
@@ -248,13 +378,22 @@ impl<T: Data> AppLauncher<T> {
 
     /// Build the windows and start the runloop.
     ///
+    /// It is fine to launch without any windows, for instance for an app that lives in
+    /// the menu bar; they can be opened later with [`DelegateCtx::new_window`], e.g. in
+    /// response to a command submitted through an [`ExtEventSink`].
+    ///
     /// Returns an error if a window cannot be instantiated. This is usually
-    /// a fatal error.
+    /// a fatal error. Otherwise this doesn't return: the process exits when the application
+    /// quits, with the status set with [`Application::set_exit_code`].
+    ///
+    /// [`Application::set_exit_code`]: crate::Application::set_exit_code
+    /// [`DelegateCtx::new_window`]: crate::DelegateCtx::new_window
     pub fn launch(mut self, data: T) -> Result<(), PlatformError> {
         let event_loop = EventLoop::with_user_event();
         let event_proxy = Arc::new(event_loop.create_proxy());
 
         let app = Application::new(event_proxy.clone())?;
+        self.ext_event_host.set_app_idle(app.get_idle_handle());
 
         let mut env = self
             .l10n_resources
@@ -274,30 +413,65 @@ impl<T: Data> AppLauncher<T> {
             event_proxy,
         );
 
+        Screen::refresh_monitors(&event_loop);
+
         for desc in self.windows {
+            let visible = desc.starts_visible();
             let window = desc.build_native(&mut state, &event_loop)?;
-            window.show();
+            if visible {
+                window.show();
+            }
         }
 
-        let mut timer_tokens = BTreeMap::new();
+        let mut timer_tokens: BTreeMap<_, Vec<(winit::window::WindowId, TimerToken)>> =
+            BTreeMap::new();
+        let mut continuous_redraw = HashSet::new();
+        // Where the mouse was when it left each window, to refresh hover state on return.
+        let mut cursor_left_at = HashMap::new();
+        let ext_event_batch = self.ext_event_batch;
+        let event_loop_mode = self.event_loop_mode;
+        let scroll_speed = self.scroll_speed;
+        let coalesce_mouse_moves = self.coalesce_mouse_moves;
+        // The latest mouse move of each window, held back until the end of the turn.
+        let mut pending_moves = HashMap::new();
+        // Keys whose press a menu hotkey consumed, so their release is dropped too.
+        let mut hotkeys_down = HashSet::new();
+        let mut last_ext_flush: Option<Instant> = None;
+        let mut ext_flush_at: Option<Instant> = None;
 
         event_loop.run(move |event, event_loop, control_flow| match event {
             winit::event::Event::NewEvents(cause) => match cause {
                 winit::event::StartCause::Init => {
-                    *control_flow = ControlFlow::Wait;
+                    *control_flow = wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
                 }
-                winit::event::StartCause::ResumeTimeReached {
-                    start,
-                    requested_resume,
-                } => {
-                    if let Some((window_id, token)) = timer_tokens.remove(&requested_resume) {
-                        state.do_winit_window_event(Event::Timer(token), &window_id);
+                winit::event::StartCause::ResumeTimeReached { .. }
+                | winit::event::StartCause::Poll => {
+                    // Several timers can share a deadline, and more than one
+                    // deadline may have passed since we went to sleep. While
+                    // redrawing continuously we poll, so check them every time.
+                    let now = std::time::Instant::now();
+                    let due: Vec<_> = timer_tokens
+                        .keys()
+                        .take_while(|instant| **instant <= now)
+                        .copied()
+                        .collect();
+                    for instant in due {
+                        let tokens = timer_tokens.remove(&instant).unwrap_or_default();
+                        for (window_id, token) in tokens {
+                            let cancelled = state
+                                .get_handle(&window_id)
+                                .map_or(false, |handle| handle.take_cancelled_timer(token));
+                            if !cancelled {
+                                state.do_winit_window_event(Event::Timer(token), &window_id);
+                            }
+                        }
                     }
-                    if let Some(instant) = timer_tokens.keys().next() {
-                        *control_flow = ControlFlow::WaitUntil(*instant);
-                    } else {
-                        *control_flow = ControlFlow::Wait;
+                    if ext_flush_at.map_or(false, |instant| instant <= now) {
+                        ext_flush_at = None;
+                        last_ext_flush = Some(now);
+                        state.idle(EXT_EVENT_IDLE_TOKEN);
                     }
+                    *control_flow = wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
                 }
                 winit::event::StartCause::WaitCancelled {
                     start,
@@ -305,54 +479,142 @@ impl<T: Data> AppLauncher<T> {
                 } => {}
                 _ => (),
             },
-            winit::event::Event::MainEventsCleared => {}
+            winit::event::Event::MainEventsCleared => {
+                for (window_id, mouse_event) in pending_moves.drain() {
+                    state.do_winit_window_event(Event::MouseMove(mouse_event), &window_id);
+                }
+                if !continuous_redraw.is_empty()
+                    && !matches!(*control_flow, ControlFlow::ExitWithCode(_))
+                {
+                    continuous_redraw.retain(|window_id| match state.get_handle(window_id) {
+                        Some(handle) => {
+                            handle.request_anim_frame();
+                            true
+                        }
+                        None => false,
+                    });
+                    if !continuous_redraw.is_empty() {
+                        *control_flow = ControlFlow::Poll;
+                    } else {
+                        *control_flow =
+                            wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
+                    }
+                }
+            }
             winit::event::Event::RedrawEventsCleared => {}
+            // The platform can end the loop by itself, e.g. on logout.
+            winit::event::Event::LoopDestroyed => state.notify_quit(),
             winit::event::Event::UserEvent(event) => match event {
                 WinitEvent::NewWindow => {
                     state.create_new_windows(event_loop);
                 }
-                WinitEvent::Idle(token) => {
+                WinitEvent::Quit => {
+                    state.notify_quit();
+                    *control_flow = ControlFlow::ExitWithCode(state.app().exit_code());
+                }
+                WinitEvent::Idle(_, EXT_EVENT_IDLE_TOKEN) => {
+                    // Handle bursts of external events in one go, instead of updating
+                    // for every single one of them.
+                    let now = std::time::Instant::now();
+                    match last_ext_flush {
+                        Some(last) if now < last + ext_event_batch => {
+                            if ext_flush_at.is_none() {
+                                ext_flush_at = Some(last + ext_event_batch);
+                                *control_flow =
+                                    wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
+                            }
+                        }
+                        _ => {
+                            last_ext_flush = Some(now);
+                            state.idle(EXT_EVENT_IDLE_TOKEN);
+                        }
+                    }
+                }
+                WinitEvent::Idle(_, token) => {
                     state.idle(token);
                 }
                 WinitEvent::Timer(window_id, token, deadline) => {
-                    let instant = std::time::Instant::now() + deadline;
-                    timer_tokens.insert(instant, (window_id, token));
-                    let instant = timer_tokens.keys().next().unwrap();
-                    *control_flow = ControlFlow::WaitUntil(*instant);
+                    timer_tokens
+                        .entry(deadline)
+                        .or_insert_with(Vec::new)
+                        .push((window_id, token));
+                    *control_flow = wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
+                }
+                WinitEvent::ContinuousRedraw(window_id, enabled) => {
+                    if enabled {
+                        continuous_redraw.insert(window_id);
+                        *control_flow = ControlFlow::Poll;
+                    } else if continuous_redraw.remove(&window_id) && continuous_redraw.is_empty() {
+                        *control_flow =
+                            wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
+                    }
+                }
+                WinitEvent::CancelTimer(window_id, token) => {
+                    if let Some(handle) = state.get_handle(&window_id) {
+                        handle.take_cancelled_timer(token);
+                    }
+                    timer_tokens.retain(|_, tokens| {
+                        tokens.retain(|t| *t != (window_id, token));
+                        !tokens.is_empty()
+                    });
+                    *control_flow = wait_for_deadline(event_loop_mode, &timer_tokens, ext_flush_at);
                 }
             },
-            winit::event::Event::LoopDestroyed => {
-                state.do_window_event(Event::ApplicationQuit, WindowId::next());
-            }
             winit::event::Event::WindowEvent { window_id, event } => match event {
                 winit::event::WindowEvent::ScaleFactorChanged {
                     scale_factor,
                     new_inner_size,
                 } => {
+                    Screen::refresh_monitors(event_loop);
+                    // With a fixed scale the widgets have nothing to hear about.
+                    if !state.has_scale_override(&window_id)
+                        && state.get_scale(&window_id) != Some(scale_factor)
+                    {
+                        let scale = Scale::new(scale_factor, scale_factor);
+                        state.do_winit_window_event(Event::WindowScale(scale), &window_id);
+                    }
                     let size = Size::new(new_inner_size.width.into(), new_inner_size.height.into());
                     let event = Event::WindowSize(size, Some(scale_factor));
                     state.do_winit_window_event(event, &window_id);
                 }
                 winit::event::WindowEvent::CloseRequested => {
-                    state.request_close_wint_window(&window_id);
-                    #[cfg(not(target_os = "macos"))]
-                    if state.windows_count() == 0 {
-                        *control_flow = ControlFlow::Exit;
+                    // Closing the last window quits, except on macOS, where apps keep running.
+                    let quits = cfg!(not(target_os = "macos")) && state.windows_count() == 1;
+                    if state.request_close_wint_window(&window_id, quits) {
+                        *control_flow = ControlFlow::ExitWithCode(state.app().exit_code());
                     }
                 }
                 winit::event::WindowEvent::Moved(pos) => {
-                    let scale = state.get_scale(&window_id).unwrap_or(1.0);
-                    let pos = Point::new(pos.x as f64 / scale, pos.y as f64 / scale);
+                    // The window may have just moved onto a monitor with a different DPI,
+                    // before we got the `ScaleFactorChanged`, so use the platform's factor
+                    // the same way `WindowHandle::get_position` and `set_position` do.
+                    let scale = state
+                        .get_handle(&window_id)
+                        .map(|handle| handle.get_scale())
+                        .unwrap_or(1.0);
+                    let pos = pos.to_logical::<f64>(scale);
+                    let pos = Point::new(pos.x, pos.y);
                     let event = Event::WindowMoved(pos);
                     state.do_winit_window_event(event, &window_id);
                 }
                 winit::event::WindowEvent::Resized(size) => {
+                    if let Some(handle) = state.get_handle(&window_id) {
+                        handle.record_resize(size);
+                    }
                     let size = Size::new(size.width.into(), size.height.into());
+                    let size = state.constrain_aspect_ratio(&window_id, size);
                     let scale = state.get_scale(&window_id).unwrap_or(1.0);
                     let event = Event::WindowSize(size, Some(scale));
                     state.do_winit_window_event(event, &window_id);
+                    state.check_window_state(&window_id);
+                }
+                winit::event::WindowEvent::Focused(focused) => {
+                    state.app().record_focus(window_id, focused);
+                    // Restoring a minimized window doesn't always resize it.
+                    state.check_window_state(&window_id);
                 }
                 winit::event::WindowEvent::ModifiersChanged(winit_mods) => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
                     let mut mods = Modifiers::empty();
                     if winit_mods.shift_key() {
                         mods.set(Modifiers::SHIFT, true);
@@ -368,21 +630,18 @@ impl<T: Data> AppLauncher<T> {
                     }
 
                     state.set_mods(&window_id, mods);
+                    let event = Event::ModifiersChanged(mods);
+                    state.do_winit_window_event(event, &window_id);
                 }
                 winit::event::WindowEvent::MouseWheel { delta, .. } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
                     let scale = state.get_scale(&window_id).unwrap_or(1.0);
                     let mods = state.get_mods(&window_id).unwrap_or(Modifiers::empty());
                     let buttons = state
                         .get_mouse_buttons(&window_id)
                         .unwrap_or(MouseButtons::new());
-                    let delta = match delta {
-                        winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                            Vec2::new(x as f64 * 32.0, -y as f64 * 32.0)
-                        }
-                        winit::event::MouseScrollDelta::PixelDelta(pos) => {
-                            Vec2::new(pos.x / scale, -pos.y / scale)
-                        }
-                    };
+                    let line_height = state.env().get(crate::theme::SCROLL_LINE_HEIGHT);
+                    let delta = wheel_delta(delta, scale, line_height, mods) * scroll_speed;
                     let pos = state.get_mouse_pos(&window_id).unwrap_or(Point::ZERO);
                     let mouse_event = MouseEvent {
                         pos,
@@ -408,7 +667,7 @@ impl<T: Data> AppLauncher<T> {
                     } else {
                         Modifiers::empty()
                     };
-                    let pos = Point::new(position.x / scale, position.y / scale);
+                    let pos = Scale::new(scale, scale).to_dp(Point::new(position.x, position.y));
                     let buttons = state
                         .get_mouse_buttons(&window_id)
                         .unwrap_or(MouseButtons::new());
@@ -422,15 +681,49 @@ impl<T: Data> AppLauncher<T> {
                         button: MouseButton::None,
                         wheel_delta: Vec2::ZERO,
                     };
-                    let event = Event::MouseMove(mouse_event);
+                    if coalesce_mouse_moves {
+                        pending_moves.insert(window_id, mouse_event);
+                    } else {
+                        let event = Event::MouseMove(mouse_event);
+                        state.do_winit_window_event(event, &window_id);
+                    }
+                }
+                winit::event::WindowEvent::CursorLeft { .. } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    if let Some(pos) = state.get_mouse_pos(&window_id) {
+                        cursor_left_at.insert(window_id, pos);
+                    }
+                    let event = Event::Internal(InternalEvent::MouseLeave);
                     state.do_winit_window_event(event, &window_id);
                 }
+                winit::event::WindowEvent::CursorEntered { .. } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    // The mouse may come back where it left without moving, and then there is
+                    // no `CursorMoved` to make the widgets under it hot again.
+                    if let Some(pos) = cursor_left_at.remove(&window_id) {
+                        let mouse_event = MouseEvent {
+                            pos,
+                            window_pos: pos,
+                            buttons: state
+                                .get_mouse_buttons(&window_id)
+                                .unwrap_or(MouseButtons::new()),
+                            mods: state.get_mods(&window_id).unwrap_or(Modifiers::empty()),
+                            count: 0,
+                            focus: false,
+                            button: MouseButton::None,
+                            wheel_delta: Vec2::ZERO,
+                        };
+                        let event = Event::MouseMove(mouse_event);
+                        state.do_winit_window_event(event, &window_id);
+                    }
+                }
                 winit::event::WindowEvent::MouseInput {
                     device_id,
                     state: mouse_state,
                     button,
                     modifiers,
                 } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
                     let mods = if let Some(mods) = state.get_mods(&window_id) {
                         mods
                     } else {
@@ -466,11 +759,14 @@ impl<T: Data> AppLauncher<T> {
                     };
                     state.do_winit_window_event(event, &window_id);
                 }
+                winit::event::WindowEvent::KeyboardInput { event, .. }
+                    if event.repeat && state.suppresses_key_repeat(&window_id) => {}
                 winit::event::WindowEvent::KeyboardInput {
                     event,
                     device_id,
                     is_synthetic,
                 } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
                     let mods = if let Some(mods) = state.get_mods(&window_id) {
                         mods
                     } else {
@@ -482,16 +778,71 @@ impl<T: Data> AppLauncher<T> {
                     };
                     let mut key_event = KeyEvent::default();
                     key_event.state = key_state;
-                    key_event.key = winit_key(event.logical_key);
+                    key_event.key = winit_key_with_text(event.logical_key, event.text);
                     key_event.code = event.physical_key;
-                    key_event.mods = mods;
+                    key_event.native_code = winit_native_code(event.physical_key);
+                    key_event.mods = winit_mods_with_text(mods, event.text);
                     key_event.repeat = event.repeat;
-                    let event = match key_event.state {
-                        KeyState::Down => Event::KeyDown(key_event),
-                        KeyState::Up => Event::KeyUp(key_event),
+                    key_event.is_composing = state.is_composing(&window_id);
+                    key_event.synthetic = is_synthetic;
+                    let handled_by_menu = match key_event.state {
+                        KeyState::Down => {
+                            let handled = !key_event.is_composing
+                                && state.handle_menu_hotkey(&key_event, &window_id);
+                            if handled {
+                                hotkeys_down.insert((window_id, key_event.code));
+                            }
+                            handled
+                        }
+                        KeyState::Up => hotkeys_down.remove(&(window_id, key_event.code)),
                     };
+                    if !handled_by_menu {
+                        let typed = key_event.clone();
+                        let event = match key_event.state {
+                            KeyState::Down => Event::KeyDown(key_event),
+                            KeyState::Up => Event::KeyUp(key_event),
+                        };
+                        let handled = state.do_winit_window_event(event, &window_id);
+                        if !handled.is_handled() && !typed.is_composing {
+                            state.type_key_text(&typed, &window_id);
+                        }
+                    }
+                }
+                winit::event::WindowEvent::TouchpadMagnify { delta, phase, .. } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    let event = Event::Zoom(delta);
+                    state.do_winit_window_event(event, &window_id);
+                    if let Some(factor) = state.zoom_gesture(&window_id, delta, phase) {
+                        state.do_winit_window_event(Event::ZoomFactor(factor), &window_id);
+                    }
+                }
+                winit::event::WindowEvent::TouchpadRotate { delta, .. } => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    let event = Event::Rotate(delta as f64);
+                    state.do_winit_window_event(event, &window_id);
+                }
+                winit::event::WindowEvent::HoveredFile(path) => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    let pos = state.get_mouse_pos(&window_id);
+                    let event = Event::FileHovered(FileDropEvent { path, pos });
+                    state.do_winit_window_event(event, &window_id);
+                }
+                winit::event::WindowEvent::DroppedFile(path) => {
+                    flush_mouse_move(&mut state, &mut pending_moves, &window_id);
+                    let pos = state.get_mouse_pos(&window_id);
+                    let event = Event::FileDropped(FileDropEvent { path, pos });
                     state.do_winit_window_event(event, &window_id);
                 }
+                winit::event::WindowEvent::HoveredFileCancelled => {
+                    state.do_winit_window_event(Event::FileHoverCancelled, &window_id);
+                }
+                winit::event::WindowEvent::ThemeChanged(theme) => {
+                    let event = Event::ThemeChanged(theme.into());
+                    state.do_winit_window_event(event, &window_id);
+                }
+                winit::event::WindowEvent::Ime(ime) => {
+                    state.do_winit_ime_event(ime, &window_id);
+                }
                 _ => (),
             },
             winit::event::Event::RedrawRequested(window_id) => {
@@ -506,12 +857,51 @@ impl<T: Data> AppLauncher<T> {
     }
 }
 
+/// Sends the mouse move held back for the window, if any, so that the events after it
+/// see the mouse where it is.
+fn flush_mouse_move<T: Data>(
+    state: &mut AppState<T>,
+    pending_moves: &mut HashMap<winit::window::WindowId, MouseEvent>,
+    window_id: &winit::window::WindowId,
+) {
+    if let Some(mouse_event) = pending_moves.remove(window_id) {
+        state.do_winit_window_event(Event::MouseMove(mouse_event), window_id);
+    }
+}
+
+/// The monitor at `index` in [`Screen::get_monitors`], or the primary one if there is none.
+fn monitor_or_primary(index: usize) -> Option<Monitor> {
+    let mut monitors = Screen::get_monitors();
+    if index < monitors.len() {
+        Some(monitors.swap_remove(index))
+    } else {
+        monitors.into_iter().find(Monitor::is_primary)
+    }
+}
+
+/// The control flow to sleep until the next timer or batch of external events is due.
+fn wait_for_deadline<V>(
+    mode: EventLoopMode,
+    timer_tokens: &BTreeMap<Instant, V>,
+    ext_flush_at: Option<Instant>,
+) -> ControlFlow {
+    if mode == EventLoopMode::Poll {
+        return ControlFlow::Poll;
+    }
+    let next_timer = timer_tokens.keys().next().copied();
+    match next_timer.into_iter().chain(ext_flush_at).min() {
+        Some(instant) => ControlFlow::WaitUntil(instant),
+        None => ControlFlow::Wait,
+    }
+}
+
 impl Default for WindowConfig {
     fn default() -> Self {
         WindowConfig {
             size_policy: WindowSizePolicy::User,
             size: None,
             min_size: None,
+            max_size: None,
             position: None,
             resizable: None,
             show_titlebar: None,
@@ -519,6 +909,20 @@ impl Default for WindowConfig {
             transparent: None,
             level: None,
             state: None,
+            icon: None,
+            resize_increments: None,
+            aspect_ratio: None,
+            theme: None,
+            visible: None,
+            background: None,
+            blur: None,
+            scale_factor: None,
+            physical_pixels: None,
+            app_id: None,
+            fullscreen_monitor: None,
+            zoom_sensitivity: None,
+            owner: None,
+            suppress_key_repeat: None,
         }
     }
 }
@@ -576,6 +980,16 @@ impl WindowConfig {
         self
     }
 
+    /// Set the window's maximum drawing area size in [display points].
+    ///
+    /// The actual maximum window size in pixels will depend on the platform DPI settings.
+    ///
+    /// [display points]: struct.Scale.html
+    pub fn with_max_size(mut self, size: impl Into<Size>) -> Self {
+        self.max_size = Some(size.into());
+        self
+    }
+
     /// Set whether the window should be resizable.
     pub fn resizable(mut self, resizable: bool) -> Self {
         self.resizable = Some(resizable);
@@ -625,6 +1039,152 @@ impl WindowConfig {
         self
     }
 
+    /// Set the window icon.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Make interactive resizing snap the window's drawing area to multiples of `size`,
+    /// in [display points], e.g. the cell size of a terminal.
+    ///
+    /// [display points]: struct.Scale.html
+    pub fn with_resize_increments(mut self, size: impl Into<Size>) -> Self {
+        self.resize_increments = Some(size.into());
+        self
+    }
+
+    /// Keep the window's drawing area at the given ratio of width to height, e.g. `16.0 / 9.0`,
+    /// while the user resizes it.
+    ///
+    /// Platforms don't support this natively, so the window is resized back to the ratio
+    /// whenever the user changes its size; it may briefly show other sizes.
+    pub fn lock_aspect_ratio(mut self, ratio: f64) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// Force the window to use a light or dark theme, instead of following the system.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Set whether the window is shown when it is created.
+    ///
+    /// A hidden window can be shown later with [`WindowHandle::show`].
+    ///
+    /// [`WindowHandle::show`]: crate::WindowHandle::show
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = Some(visible);
+        self
+    }
+
+    /// Set the color the window is cleared to before its widgets are painted.
+    ///
+    /// This is useful when the widgets don't cover the whole window, e.g. to match a
+    /// dark theme from the very first frame.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Set whether the compositor should blur what is behind the window.
+    ///
+    /// This only makes a difference for a [`transparent`] window, and only where the
+    /// platform supports it; see [`WindowHandle::set_blur`].
+    ///
+    /// [`transparent`]: WindowConfig::transparent
+    /// [`WindowHandle::set_blur`]: crate::WindowHandle::set_blur
+    pub fn with_blur(mut self, blur: bool) -> Self {
+        self.blur = Some(blur);
+        self
+    }
+
+    /// Use a fixed scale factor for this window, instead of the one of the display it is on.
+    ///
+    /// Widgets are laid out and painted at this [`Scale`], and mouse positions are converted
+    /// with it, whatever the platform reports. This makes rendering reproducible, e.g. for
+    /// screenshots in CI, or lets a kiosk display use a larger scale than the system's.
+    ///
+    /// The window's own size and position are still set in the platform's display points.
+    ///
+    /// [`Scale`]: crate::Scale
+    pub fn with_scale_factor(mut self, scale: f64) -> Self {
+        self.scale_factor = Some(scale);
+        self
+    }
+
+    /// Lay out and paint this window in physical pixels, ignoring the display's scale factor.
+    ///
+    /// The window's [`Scale`] is always 1.0, so one unit of widget space is one pixel on
+    /// the screen, and mouse positions and the window size reported to widgets are in
+    /// pixels too. This keeps pixel art sharp, which would otherwise be smoothed when
+    /// scaled to the display.
+    ///
+    /// Nothing is scaled up on high DPI displays, so text and widgets look small there.
+    /// This takes precedence over [`with_scale_factor`]; like with it, the window's own
+    /// size and position are still set in the platform's display points.
+    ///
+    /// [`Scale`]: crate::Scale
+    /// [`with_scale_factor`]: WindowConfig::with_scale_factor
+    pub fn use_physical_pixels(mut self, physical: bool) -> Self {
+        self.physical_pixels = Some(physical);
+        self
+    }
+
+    /// Set the application id that Linux desktops use to group the window in the taskbar
+    /// and find its icon, usually the name of the app's `.desktop` file.
+    ///
+    /// This can only be set when the window is created.
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// Open the window borderless-fullscreen on the monitor at `index` in
+    /// [`Screen::get_monitors`], or on the primary monitor if there is no such monitor.
+    ///
+    /// This overrides a [`WindowState`] set with [`set_window_state`].
+    ///
+    /// [`set_window_state`]: WindowConfig::set_window_state
+    pub fn fullscreen_on_monitor(mut self, index: usize) -> Self {
+        self.fullscreen_monitor = Some(index);
+        self
+    }
+
+    /// Set how strongly pinch gestures zoom, as a multiple of the platform's deltas.
+    ///
+    /// This scales the factor of [`Event::ZoomFactor`]; the default is `1.0`.
+    pub fn zoom_sensitivity(mut self, sensitivity: f64) -> Self {
+        self.zoom_sensitivity = Some(sensitivity);
+        self
+    }
+
+    /// Make the window owned by the open window `owner`, as for a popup or a tool window.
+    ///
+    /// The window is closed when its owner is, and hidden while its owner is minimized.
+    /// On Windows it also stays in front of its owner. This is only applied when the window
+    /// is built.
+    ///
+    /// Closing with the owner skips [`Event::WindowCloseRequested`], so the window can't
+    /// refuse; it still gets [`Event::WindowDisconnected`]. A window the application hid
+    /// itself stays hidden when its owner is restored.
+    pub fn with_owner(mut self, owner: WindowId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Set whether keys held down send a single [`Event::KeyDown`] until they are released,
+    /// instead of repeating it.
+    ///
+    /// This suits a game, or a synthesizer that would retrigger a note on every repeat.
+    /// Repeating is on by default, as text fields need it.
+    pub fn suppress_key_repeat(mut self, suppress: bool) -> Self {
+        self.suppress_key_repeat = Some(suppress);
+        self
+    }
+
     /// Apply this window configuration to the passed in WindowBuilder
     pub fn apply_to_builder(&self, builder: WindowBuilder) -> WindowBuilder {
         let mut builder = if let Some(resizable) = self.resizable {
@@ -639,10 +1199,17 @@ impl WindowConfig {
             builder
         };
 
-        let builder = if let Some(size) = self.size {
+        let builder = if let WindowSizePolicy::Content = self.size_policy {
+            // The first layout sets the real size; until then, don't open below the minimum.
+            // This includes the 1x1 seed `window_size_policy` sets on Windows.
+            let seed = self.size.unwrap_or(Size::ZERO);
+            let min_size = self.min_size.unwrap_or(Size::ZERO);
+            builder.set_size(Size::new(
+                seed.width.max(min_size.width),
+                seed.height.max(min_size.height),
+            ))
+        } else if let Some(size) = self.size {
             builder.set_size(size)
-        } else if let WindowSizePolicy::Content = self.size_policy {
-            builder.set_size(Size::new(0., 0.))
         } else {
             builder
         };
@@ -667,10 +1234,58 @@ impl WindowConfig {
             builder
         };
 
-        if let Some(min_size) = self.min_size {
+        let builder = if let Some(icon) = &self.icon {
+            builder.set_window_icon(icon.clone())
+        } else {
+            builder
+        };
+
+        let builder = if let Some(index) = self.fullscreen_monitor {
+            builder.set_fullscreen(Fullscreen::Borderless(monitor_or_primary(index)))
+        } else {
+            builder
+        };
+
+        let builder = if let Some(visible) = self.visible {
+            builder.set_visible(visible)
+        } else {
+            builder
+        };
+
+        let builder = if let Some(blur) = self.blur {
+            builder.set_blur(blur)
+        } else {
+            builder
+        };
+
+        let builder = if let Some(app_id) = &self.app_id {
+            builder.set_app_id(app_id.clone())
+        } else {
+            builder
+        };
+
+        let builder = if let Some(min_size) = self.min_size {
             builder.set_min_size(min_size)
         } else {
             builder
+        };
+
+        let builder = if let Some(max_size) = self.max_size {
+            builder.set_max_size(max_size)
+        } else {
+            builder
+        };
+
+        let builder = if let Some(increments) = self.resize_increments {
+            builder.set_resize_increments(increments)
+        } else {
+            builder
+        };
+
+        if let Some(theme) = self.theme {
+            builder.set_theme(theme)
+        } else {
+            builder
         }
     }
 
@@ -692,8 +1307,21 @@ impl WindowConfig {
             win_handle.set_size(size);
         }
 
-        // Can't apply min size currently as window handle
-        // does not support it.
+        if let Some(min_size) = self.min_size {
+            win_handle.set_min_size(min_size);
+        }
+
+        if let Some(max_size) = self.max_size {
+            win_handle.set_max_size(max_size);
+        }
+
+        if let Some(increments) = self.resize_increments {
+            win_handle.set_resize_increments(Some(increments));
+        }
+
+        if let Some(theme) = self.theme {
+            win_handle.set_theme(Some(theme));
+        }
 
         if let Some(position) = self.position {
             win_handle.set_position(position);
@@ -706,6 +1334,24 @@ impl WindowConfig {
         if let Some(state) = self.state {
             win_handle.set_window_state(state);
         }
+
+        if let Some(icon) = &self.icon {
+            win_handle.set_window_icon(Some(icon.clone()));
+        }
+
+        if let Some(index) = self.fullscreen_monitor {
+            win_handle.set_fullscreen(Some(Fullscreen::Borderless(monitor_or_primary(index))));
+        }
+
+        if let Some(blur) = self.blur {
+            win_handle.set_blur(blur);
+        }
+
+        match self.visible {
+            Some(true) => win_handle.show(),
+            Some(false) => win_handle.hide(),
+            None => (),
+        }
     }
 }
 
@@ -751,6 +1397,9 @@ impl<T: Data> WindowDesc<T> {
     /// `menu` is a callback for creating the menu. Its first argument is the id of the window that
     /// will have the menu, or `None` if it's creating the root application menu for an app with no
     /// menus (which can happen, for example, on macOS).
+    ///
+    /// The menu isn't shown as a menu bar, since there are no native menus yet; only the
+    /// hotkeys of its items send their commands.
     pub fn menu(
         mut self,
         menu: impl FnMut(Option<WindowId>, &T, &Env) -> Menu<T> + 'static,
@@ -811,6 +1460,14 @@ impl<T: Data> WindowDesc<T> {
         self
     }
 
+    /// Set the window's maximum drawing area size in [display points].
+    ///
+    /// [display points]: struct.Scale.html
+    pub fn with_max_size(mut self, size: impl Into<Size>) -> Self {
+        self.config = self.config.with_max_size(size);
+        self
+    }
+
     /// Builder-style method to set whether this window can be resized.
     pub fn resizable(mut self, resizable: bool) -> Self {
         self.config = self.config.resizable(resizable);
@@ -855,6 +1512,108 @@ impl<T: Data> WindowDesc<T> {
         self
     }
 
+    /// Builder-style method to set the window icon.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.config = self.config.with_icon(icon);
+        self
+    }
+
+    /// Make interactive resizing snap the window's drawing area to multiples of `size`,
+    /// in [display points].
+    ///
+    /// [display points]: struct.Scale.html
+    pub fn with_resize_increments(mut self, size: impl Into<Size>) -> Self {
+        self.config = self.config.with_resize_increments(size);
+        self
+    }
+
+    /// Keep the window's drawing area at the given ratio of width to height while the user
+    /// resizes it.
+    pub fn lock_aspect_ratio(mut self, ratio: f64) -> Self {
+        self.config = self.config.lock_aspect_ratio(ratio);
+        self
+    }
+
+    /// Force the window to use a light or dark theme, instead of following the system.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.config = self.config.with_theme(theme);
+        self
+    }
+
+    /// Set whether the window is shown when it is created.
+    ///
+    /// This can be used to build a window at launch, but only show it with
+    /// [`WindowHandle::show`] once it is needed, e.g. after a splash screen.
+    ///
+    /// [`WindowHandle::show`]: crate::WindowHandle::show
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.config = self.config.with_visible(visible);
+        self
+    }
+
+    /// Set the color the window is cleared to before its widgets are painted.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.config = self.config.with_background(color);
+        self
+    }
+
+    /// Set whether the compositor should blur what is behind the window, for a
+    /// frosted-glass look through a [`transparent`] window.
+    ///
+    /// [`transparent`]: WindowDesc::transparent
+    pub fn with_blur(mut self, blur: bool) -> Self {
+        self.config = self.config.with_blur(blur);
+        self
+    }
+
+    /// Use a fixed scale factor for this window, see [`WindowConfig::with_scale_factor`].
+    pub fn with_scale_factor(mut self, scale: f64) -> Self {
+        self.config = self.config.with_scale_factor(scale);
+        self
+    }
+
+    /// Work in physical pixels in this window, see [`WindowConfig::use_physical_pixels`].
+    pub fn use_physical_pixels(mut self, physical: bool) -> Self {
+        self.config = self.config.use_physical_pixels(physical);
+        self
+    }
+
+    /// Set the application id of this window, see [`WindowConfig::with_app_id`].
+    pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.config = self.config.with_app_id(app_id);
+        self
+    }
+
+    /// Open the window fullscreen on the monitor at `index`, see
+    /// [`WindowConfig::fullscreen_on_monitor`].
+    pub fn fullscreen_on_monitor(mut self, index: usize) -> Self {
+        self.config = self.config.fullscreen_on_monitor(index);
+        self
+    }
+
+    /// Set how strongly pinch gestures zoom, see [`WindowConfig::zoom_sensitivity`].
+    pub fn zoom_sensitivity(mut self, sensitivity: f64) -> Self {
+        self.config = self.config.zoom_sensitivity(sensitivity);
+        self
+    }
+
+    /// Make the window owned by another one, see [`WindowConfig::with_owner`].
+    pub fn with_owner(mut self, owner: WindowId) -> Self {
+        self.config = self.config.with_owner(owner);
+        self
+    }
+
+    /// Set whether held keys repeat, see [`WindowConfig::suppress_key_repeat`].
+    pub fn suppress_key_repeat(mut self, suppress: bool) -> Self {
+        self.config = self.config.suppress_key_repeat(suppress);
+        self
+    }
+
+    /// Returns `false` if the window was configured to start hidden.
+    pub(crate) fn starts_visible(&self) -> bool {
+        self.config.visible != Some(false)
+    }
+
     /// Set the [`WindowConfig`] of window.
     pub fn with_config(mut self, config: WindowConfig) -> Self {
         self.config = config;
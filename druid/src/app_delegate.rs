@@ -15,10 +15,11 @@
 //! Customizing application-level behaviour.
 
 use std::any::{Any, TypeId};
+use std::collections::HashMap;
 
 use crate::{
-    commands, core::CommandQueue, ext_event::ExtEventHost, Command, Data, Env, Event, ExtEventSink,
-    Handled, SingleUse, Target, WindowDesc, WindowId,
+    commands, core::CommandQueue, ext_event::ExtEventHost, Application, Command, Data, Env, Event,
+    ExtEventSink, Handled, SingleUse, Target, Widget, WindowDesc, WindowHandle, WindowId,
 };
 
 /// A context passed in to [`AppDelegate`] functions.
@@ -28,6 +29,15 @@ pub struct DelegateCtx<'a> {
     pub(crate) command_queue: &'a mut CommandQueue,
     pub(crate) ext_event_host: &'a ExtEventHost,
     pub(crate) app_data_type: TypeId,
+    pub(crate) winit_windows: &'a HashMap<winit::window::WindowId, WindowId>,
+    /// The open windows, for their handles.
+    pub(crate) windows: &'a dyn WindowHandles,
+}
+
+/// Gives the delegate the handles of the open windows, without it knowing their data type.
+pub(crate) trait WindowHandles {
+    fn ids(&self) -> Box<dyn Iterator<Item = WindowId> + '_>;
+    fn handle(&self, id: WindowId) -> Option<&WindowHandle>;
 }
 
 impl<'a> DelegateCtx<'a> {
@@ -54,6 +64,49 @@ impl<'a> DelegateCtx<'a> {
         self.ext_event_host.make_sink()
     }
 
+    /// Returns the [`WindowId`] of the open window that winit knows by `winit_id`.
+    ///
+    /// This lines up events from winit with druid's windows, for code that also talks to
+    /// winit directly. [`WindowHandle::id`] goes the other way.
+    ///
+    /// [`WindowHandle::id`]: crate::WindowHandle::id
+    pub fn window_id_from_winit(&self, winit_id: winit::window::WindowId) -> Option<WindowId> {
+        self.winit_windows.get(&winit_id).copied()
+    }
+
+    /// Returns winit's id for the open window with the given [`WindowId`].
+    pub fn winit_window_id(&self, id: WindowId) -> Option<winit::window::WindowId> {
+        self.winit_windows
+            .iter()
+            .find(|(_, window_id)| **window_id == id)
+            .map(|(winit_id, _)| *winit_id)
+    }
+
+    /// Returns the id of the window that has keyboard focus, if any.
+    ///
+    /// Use this to resolve commands sent to [`Target::Global`], like those from the
+    /// application menu on macOS, that should act on the active window.
+    pub fn focused_window(&self) -> Option<WindowId> {
+        Application::try_global()
+            .and_then(|app| app.focused_window())
+            .and_then(|winit_id| self.window_id_from_winit(winit_id))
+    }
+
+    /// Returns the ids of the open windows, in no particular order.
+    ///
+    /// Together with [`window_handle`] this is enough to build a "Window" menu that lists
+    /// the open documents by title and brings one of them to the front.
+    ///
+    /// [`window_handle`]: DelegateCtx::window_handle
+    pub fn windows(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows.ids()
+    }
+
+    /// Returns the handle of the open window with the given id.
+    pub fn window_handle(&self, id: WindowId) -> Option<&WindowHandle> {
+        self.windows.handle(id)
+    }
+
     /// Create a new window.
     /// `T` must be the application's root `Data` type (the type provided to [`AppLauncher::launch`]).
     ///
@@ -69,6 +122,28 @@ impl<'a> DelegateCtx<'a> {
             debug_panic!("DelegateCtx::new_window<T> - T must match the application data type.");
         }
     }
+
+    /// Replace the root widget of an open window, see [`EventCtx::replace_root_widget`].
+    ///
+    /// [`EventCtx::replace_root_widget`]: crate::EventCtx::replace_root_widget
+    pub fn replace_root_widget<T: Any>(
+        &mut self,
+        window: WindowId,
+        root: impl Widget<T> + 'static,
+    ) {
+        if self.app_data_type == TypeId::of::<T>() {
+            let root: Box<dyn Widget<T>> = Box::new(root);
+            self.submit_command(
+                commands::REPLACE_ROOT_WIDGET
+                    .with(SingleUse::new(Box::new(root)))
+                    .to(Target::Window(window)),
+            );
+        } else {
+            debug_panic!(
+                "DelegateCtx::replace_root_widget<T> - T must match the application data type."
+            );
+        }
+    }
 }
 
 /// A type that provides hooks for handling and modifying top-level events.
@@ -133,4 +208,26 @@ pub trait AppDelegate<T: Data> {
     /// The handler for window deletion events.
     /// This function is called after a window has been removed.
     fn window_removed(&mut self, id: WindowId, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called on the main thread after [`ExtEventSink::wake`] was called from anywhere.
+    ///
+    /// This is a good place to drain a queue that is shared with another thread or an
+    /// async runtime, without having to go through a [`Command`].
+    ///
+    /// [`ExtEventSink::wake`]: crate::ExtEventSink::wake
+    /// [`Command`]: struct.Command.html
+    fn wake(&mut self, data: &mut T, env: &Env, ctx: &mut DelegateCtx) {}
+
+    /// Called before the application quits, either because the last window was closed
+    /// or because [`QUIT_APP`] was submitted.
+    ///
+    /// Return `Handled::Yes` to keep the application running, for instance to ask about
+    /// unsaved work first. Otherwise this is the last chance to save state, before
+    /// [`Event::ApplicationQuit`] is sent to the windows that are still open.
+    ///
+    /// [`QUIT_APP`]: crate::commands::QUIT_APP
+    /// [`Event::ApplicationQuit`]: crate::Event::ApplicationQuit
+    fn quit_requested(&mut self, data: &mut T, env: &Env, ctx: &mut DelegateCtx) -> Handled {
+        Handled::No
+    }
 }
@@ -201,6 +201,11 @@ pub mod sys {
     pub(crate) const NEW_WINDOW: Selector<SingleUse<Box<dyn Any>>> =
         Selector::new("druid-builtin.new-window");
 
+    /// The selector for a command to replace the root widget of a window. The payload is
+    /// a `Box<dyn Widget<T>>`.
+    pub(crate) const REPLACE_ROOT_WIDGET: Selector<SingleUse<Box<dyn Any>>> =
+        Selector::new("druid-builtin.replace-root-widget");
+
     /// The selector for a command to close a window.
     ///
     /// The command must target a specific window.
@@ -23,7 +23,7 @@ use std::{
     collections::VecDeque,
     ops::{Deref, DerefMut},
     rc::Rc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tracing::{error, trace, warn};
 
@@ -504,6 +504,20 @@ impl_context_method!(
             trace!("request_timer deadline={:?}", deadline);
             self.state.request_timer(&mut self.widget_state, deadline)
         }
+
+        /// Request a timer event at a point in time.
+        ///
+        /// Unlike with [`request_timer`], deadlines computed from the previous one don't
+        /// drift when an event is handled late, which keeps e.g. the ticks of a clock
+        /// aligned with the seconds.
+        ///
+        /// [`request_timer`]: Self::request_timer
+        pub fn request_timer_at(&mut self, deadline: Instant) -> TimerToken {
+            trace!("request_timer_at deadline={:?}", deadline);
+            let timer_token = self.state.window.request_timer_at(deadline);
+            self.widget_state.add_timer(timer_token);
+            timer_token
+        }
     }
 );
 
@@ -563,6 +577,28 @@ impl EventCtx<'_, '_> {
         }
     }
 
+    /// Replace the root widget of the current window, e.g. to go from a login screen to the
+    /// main UI without opening another window.
+    ///
+    /// The whole widget tree of the window is dropped, this widget included, once the
+    /// current event has been handled; the new tree starts out without focus.
+    /// `T` must be the application's root `Data` type.
+    pub fn replace_root_widget<T: Any>(&mut self, root: impl Widget<T> + 'static) {
+        trace!("replace_root_widget");
+        if self.state.root_app_data_type == TypeId::of::<T>() {
+            let root: Box<dyn Widget<T>> = Box::new(root);
+            self.submit_command(
+                commands::REPLACE_ROOT_WIDGET
+                    .with(SingleUse::new(Box::new(root)))
+                    .to(Target::Window(self.state.window_id)),
+            );
+        } else {
+            debug_panic!(
+                "EventCtx::replace_root_widget<T> - T must match the application data type."
+            );
+        }
+    }
+
     /// Configure the current window
     pub fn configure_window(&mut self, window_config: WindowConfig) {
         trace!("configire_window");
@@ -730,12 +730,14 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                 }
                 true
             }
-            Event::WindowSize(_, _) => {
+            Event::WindowSize(_, _) | Event::WindowScale(_) => {
                 self.state.needs_layout = true;
                 true
             }
             Event::ApplicationQuit => true,
             Event::WindowMoved(_) => true,
+            Event::WindowState(_) => true,
+            Event::ThemeChanged(_) => true,
             Event::MouseDown(mouse_event) => {
                 WidgetPod::set_hot_state(
                     &mut self.inner,
@@ -815,6 +817,20 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
                     false
                 }
             }
+            Event::FileHovered(drop) | Event::FileDropped(drop) => match drop.pos {
+                Some(pos) if rect.winding(pos) != 0 => {
+                    let mut drop = drop.clone();
+                    drop.pos = Some(pos - rect.origin().to_vec2());
+                    modified_event = Some(match event {
+                        Event::FileHovered(_) => Event::FileHovered(drop),
+                        _ => Event::FileDropped(drop),
+                    });
+                    true
+                }
+                Some(_) => false,
+                None => true,
+            },
+            Event::FileHoverCancelled => true,
             Event::AnimFrame(_) => {
                 let r = self.state.request_anim;
                 self.state.request_anim = false;
@@ -822,8 +838,15 @@ impl<T: Data, W: Widget<T>> WidgetPod<T, W> {
             }
             Event::KeyDown(_) => self.state.has_focus,
             Event::KeyUp(_) => self.state.has_focus,
+            Event::ImeComposition(_) => self.state.has_focus,
+            Event::FocusNext | Event::FocusPrev => {
+                self.state.has_focus || ctx.state.focus_widget.is_none()
+            }
+            Event::ModifiersChanged(_) => had_active || self.state.is_hot || self.state.has_focus,
             Event::Paste(_) => self.state.has_focus,
-            Event::Zoom(_) => had_active || self.state.is_hot,
+            Event::Zoom(_) | Event::ZoomFactor(_) | Event::Rotate(_) => {
+                had_active || self.state.is_hot
+            }
             Event::Timer(_) => false, // This event was targeted only to our parent
             Event::ImeStateChange => true, // once delivered to the focus widget, recurse to the component?
             Event::Command(_) => true,
@@ -14,9 +14,12 @@
 
 //! Events.
 
+use std::ops::Range;
+use std::path::PathBuf;
+
 use crate::kurbo::{Point, Rect, Shape, Size, Vec2};
 
-use druid_shell::{Clipboard, KeyEvent, TimerToken};
+use druid_shell::{Clipboard, KeyEvent, Modifiers, Scale, Theme, TimerToken, WindowState};
 
 use crate::mouse::MouseEvent;
 use crate::{Command, Notification, WidgetId};
@@ -50,6 +53,16 @@ use crate::{Command, Notification, WidgetId};
 /// [`WidgetPod`]: struct.WidgetPod.html
 #[derive(Debug, Clone)]
 pub enum Event {
+    /// Sent to all widgets in every open window when the application is about to quit.
+    ///
+    /// The [`AppDelegate`] can still prevent this in [`quit_requested`], unless the
+    /// application quits because [`Application::quit`] was called directly; submit
+    /// [`QUIT_APP`] to give it the chance.
+    ///
+    /// [`AppDelegate`]: crate::AppDelegate
+    /// [`quit_requested`]: crate::AppDelegate::quit_requested
+    /// [`Application::quit`]: crate::Application::quit
+    /// [`QUIT_APP`]: crate::commands::QUIT_APP
     ApplicationQuit,
     /// Sent to all widgets in a given window when that window is first instantiated.
     ///
@@ -68,7 +81,11 @@ pub enum Event {
     /// that the window *will* close just because this event is received; for instance, you should
     /// avoid destructive side effects such as cleaning up resources.
     ///
+    /// A widget that vetoes the close, for example to ask about unsaved changes, can close the
+    /// window later by submitting [`CLOSE_WINDOW`].
+    ///
     /// [`set_handled`]: crate::EventCtx::set_handled
+    /// [`CLOSE_WINDOW`]: crate::commands::CLOSE_WINDOW
     WindowCloseRequested,
     /// Sent to all widgets in a given window when the system is going to close that window.
     ///
@@ -81,9 +98,32 @@ pub enum Event {
     /// widgets. It *is* propagated through the RootWidget and handled
     /// in the WindowPod, but after that it might be considered better
     /// to just handle it in `layout`.
+    ///
+    /// To tell a resize made by the application from one made by the user, check
+    /// [`WindowHandle::resize_was_requested`].
+    ///
+    /// [`WindowHandle::resize_was_requested`]: crate::WindowHandle::resize_was_requested
     WindowSize(Size, Option<f64>),
+    /// Sent to all widgets in a given window when the window's scale factor changes, for
+    /// example because it was moved to a monitor with a different DPI.
+    ///
+    /// This always arrives before the [`WindowSize`] event carrying the new scale.
+    ///
+    /// [`WindowSize`]: Event::WindowSize
+    WindowScale(Scale),
     /// Called when the window get moved
     WindowMoved(Point),
+    /// Sent to all widgets in a given window when the window is maximized, minimized,
+    /// restored or made fullscreen, whether by the user or by the application.
+    WindowState(WindowState),
+    /// Sent to all widgets in a given window when the theme of the window changes, usually
+    /// because the user switched the system between light and dark mode.
+    ///
+    /// An [`AppDelegate`] can use this to update the colors in the [`Env`].
+    ///
+    /// [`AppDelegate`]: crate::AppDelegate
+    /// [`Env`]: crate::Env
+    ThemeChanged(Theme),
     /// Called when a mouse button is pressed.
     MouseDown(MouseEvent),
     /// Called when a mouse button is released.
@@ -104,6 +144,12 @@ pub enum Event {
     /// [`set_cursor`]: struct.EventCtx.html#method.set_cursor
     MouseMove(MouseEvent),
     /// Called when the mouse wheel or trackpad is scrolled.
+    ///
+    /// Turning a plain vertical wheel while Shift is held scrolls horizontally, so the
+    /// delta is in `wheel_delta.x`, as on most platforms.
+    ///
+    /// The delta is in display points for both line-based wheels and pixel-based
+    /// trackpads; see [`wheel_delta`](druid_shell::wheel_delta).
     Wheel(MouseEvent),
     /// Called when a key is pressed.
     KeyDown(KeyEvent),
@@ -112,12 +158,72 @@ pub enum Event {
     /// Because of repeat, there may be a number `KeyDown` events before
     /// a corresponding `KeyUp` is sent.
     KeyUp(KeyEvent),
+    /// Sent after a Tab [`KeyDown`] that no widget handled, to move the focus forward.
+    ///
+    /// Like key events, this goes to the focused widget and its ancestors, or to every
+    /// widget if nothing has focus. A container can handle it to pick the next widget
+    /// itself; otherwise the focus moves to the next widget in the focus chain, see
+    /// [`LifeCycle::BuildFocusChain`].
+    ///
+    /// [`KeyDown`]: Event::KeyDown
+    FocusNext,
+    /// Sent after a Shift-Tab [`KeyDown`] that no widget handled, to move the focus back.
+    ///
+    /// See [`FocusNext`].
+    ///
+    /// [`KeyDown`]: Event::KeyDown
+    /// [`FocusNext`]: Event::FocusNext
+    FocusPrev,
+    /// Called when a modifier key is pressed or released.
+    ///
+    /// This is sent even when no other input accompanies the change, so a widget can
+    /// update a cursor or an overlay as soon as, say, Shift is held down. Like
+    /// [`Wheel`], it is propagated to the active and hot widgets, and also to the
+    /// focused widget.
+    ///
+    /// [`Wheel`]: Event::Wheel
+    ModifiersChanged(Modifiers),
     /// Called when a paste command is received.
     Paste(Clipboard),
     /// Called when the trackpad is pinched.
     ///
-    /// The value is a delta.
+    /// The value is a delta: positive when the fingers move apart (zoom in) and negative
+    /// when they move together. The platform doesn't report a focal point; like [`Wheel`],
+    /// this is delivered to the widgets under the mouse, so use the last mouse position.
+    ///
+    /// [`Wheel`]: Event::Wheel
     Zoom(f64),
+    /// Called with the zoom factor of the current pinch gesture, after each [`Zoom`].
+    ///
+    /// The factor starts at `1.0` with each gesture and builds up from its deltas, scaled
+    /// with the window's [`zoom_sensitivity`]. A widget zooming in or out with the fingers
+    /// multiplies the zoom it had when the gesture started by this factor.
+    ///
+    /// [`Zoom`]: Event::Zoom
+    /// [`zoom_sensitivity`]: crate::WindowConfig::zoom_sensitivity
+    ZoomFactor(f64),
+    /// Called when two fingers are rotated on the trackpad.
+    ///
+    /// The value is a delta in degrees: positive for a counter-clockwise rotation and
+    /// negative for a clockwise one. It is delivered to the same widgets as [`Zoom`].
+    ///
+    /// [`Zoom`]: Event::Zoom
+    Rotate(f64),
+    /// Called while a file is dragged over the window, once for each file being dragged.
+    ///
+    /// If the position of the drag is known, this is delivered to the widgets under it,
+    /// with the position in the widget's coordinate space; otherwise it is sent to
+    /// every widget.
+    FileHovered(FileDropEvent),
+    /// Called when a file is dropped onto the window, once for each file.
+    ///
+    /// This is routed the same way as [`FileHovered`].
+    ///
+    /// [`FileHovered`]: Event::FileHovered
+    FileDropped(FileDropEvent),
+    /// Called on every widget when files dragged over the window leave it, or the drag is
+    /// aborted, without being dropped.
+    FileHoverCancelled,
     /// Called on a timer event.
     ///
     /// Request a timer event through [`EventCtx::request_timer()`]. That will
@@ -135,6 +241,10 @@ pub enum Event {
     /// will be 0. (This logic is presently per-window but might change to
     /// per-widget to make it more consistent). Otherwise it is in nanoseconds.
     ///
+    /// Advance animations by `interval` rather than by a fixed step, so that they
+    /// run at the same speed regardless of the frame rate. Any changes to the data
+    /// are visible in the frame painted right after.
+    ///
     /// The `paint` method will be called shortly after this event is finished.
     /// As a result, you should try to avoid doing anything computationally
     /// intensive in response to an `AnimFrame` event: it might make Druid miss
@@ -188,6 +298,18 @@ pub enum Event {
     /// should check the shared state, perform invalidation, and update `Data`
     /// as necessary.
     ImeStateChange,
+    /// Sent to the focused widget when the text being composed with the IME changes.
+    ///
+    /// This lets a widget that draws text itself show the composition inline, e.g.
+    /// underlined at the caret. The composition ends with an event with empty text,
+    /// which is sent when the text is committed or the IME is turned off.
+    ///
+    /// The platform only draws the IME candidate window; showing the composition is up
+    /// to the application. Windows without a focused text field enable the IME with
+    /// [`WindowHandle::set_ime_allowed`].
+    ///
+    /// [`WindowHandle::set_ime_allowed`]: crate::WindowHandle::set_ime_allowed
+    ImeComposition(ImeComposition),
     /// Internal druid event.
     ///
     /// This should always be passed down to descendant [`WidgetPod`]s.
@@ -196,6 +318,24 @@ pub enum Event {
     Internal(InternalEvent),
 }
 
+/// A file being dragged over or dropped onto a window.
+#[derive(Debug, Clone)]
+pub struct FileDropEvent {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The position of the drag, if the platform reported the mouse position.
+    pub pos: Option<Point>,
+}
+
+/// The text being composed with the IME, see [`Event::ImeComposition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImeComposition {
+    /// The text being composed, empty once the composition is over.
+    pub text: String,
+    /// The caret within `text` as a byte range, or `None` to hide the caret.
+    pub cursor: Option<Range<usize>>,
+}
+
 /// Internal events used by druid inside [`WidgetPod`].
 ///
 /// These events are translated into regular [`Event`]s
@@ -408,6 +548,18 @@ impl Event {
                     None
                 }
             }
+            Event::FileHovered(drop) | Event::FileDropped(drop) => match drop.pos {
+                Some(pos) if force || viewport.winding(pos) != 0 => {
+                    let mut drop = drop.clone();
+                    drop.pos = Some(pos + offset);
+                    Some(match self {
+                        Event::FileHovered(_) => Event::FileHovered(drop),
+                        _ => Event::FileDropped(drop),
+                    })
+                }
+                Some(_) => None,
+                None => Some(self.clone()),
+            },
             _ => Some(self.clone()),
         }
     }
@@ -436,7 +588,11 @@ impl Event {
             | Event::WindowCloseRequested
             | Event::WindowDisconnected
             | Event::WindowSize(_, _)
+            | Event::WindowScale(_)
             | Event::WindowMoved(_)
+            | Event::WindowState(_)
+            | Event::ThemeChanged(_)
+            | Event::FileHoverCancelled
             | Event::Timer(_)
             | Event::AnimFrame(_)
             | Event::Command(_)
@@ -448,11 +604,42 @@ impl Event {
             | Event::Wheel(_)
             | Event::KeyDown(_)
             | Event::KeyUp(_)
+            | Event::FocusNext
+            | Event::FocusPrev
+            | Event::ModifiersChanged(_)
             | Event::Paste(_)
             | Event::ImeStateChange
-            | Event::Zoom(_) => false,
+            | Event::ImeComposition(_)
+            | Event::Zoom(_)
+            | Event::ZoomFactor(_)
+            | Event::Rotate(_)
+            | Event::FileHovered(_)
+            | Event::FileDropped(_) => false,
         }
     }
+
+    /// Whether this event comes from the user interacting with the window, as opposed to
+    /// the window or the application changing.
+    ///
+    /// These are the events a disabled window ignores.
+    pub(crate) fn is_user_input(&self) -> bool {
+        matches!(
+            self,
+            Event::MouseDown(_)
+                | Event::MouseUp(_)
+                | Event::MouseMove(_)
+                | Event::Wheel(_)
+                | Event::KeyDown(_)
+                | Event::KeyUp(_)
+                | Event::Paste(_)
+                | Event::ImeComposition(_)
+                | Event::Zoom(_)
+                | Event::ZoomFactor(_)
+                | Event::Rotate(_)
+                | Event::FileHovered(_)
+                | Event::FileDropped(_)
+        )
+    }
 }
 
 impl LifeCycle {
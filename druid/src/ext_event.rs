@@ -19,7 +19,7 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
 use crate::shell::IdleHandle;
-use crate::win_handler::EXT_EVENT_IDLE_TOKEN;
+use crate::win_handler::{EXT_EVENT_IDLE_TOKEN, EXT_WAKE_IDLE_TOKEN};
 use crate::{command::SelectorSymbol, Command, Selector, Target, WindowId};
 
 pub(crate) type ExtCommand = (SelectorSymbol, Box<dyn Any + Send>, Target);
@@ -66,6 +66,15 @@ impl ExtEventHost {
         }
     }
 
+    /// Use an application-wide handle until a window provides one, so that we can
+    /// be woken up before the first window opens.
+    pub(crate) fn set_app_idle(&mut self, mut handle: IdleHandle) {
+        if self.has_pending_items() {
+            handle.schedule_idle(EXT_EVENT_IDLE_TOKEN);
+        }
+        self.handle.lock().unwrap().replace(handle);
+    }
+
     pub(crate) fn set_idle(&mut self, handle: IdleHandle, window_id: WindowId) {
         self.handle.lock().unwrap().replace(handle);
         self.handle_window_id = Some(window_id);
@@ -107,16 +116,40 @@ impl ExtEventSink {
     ) -> Result<(), ExtEventError> {
         let target = target.into();
         let payload = payload.into();
-        self.queue.lock().map_err(|_| ExtEventError)?.push_back((
-            selector.symbol(),
-            payload,
-            target,
-        ));
-        if let Some(handle) = self.handle.lock().unwrap().as_mut() {
-            handle.schedule_idle(EXT_EVENT_IDLE_TOKEN);
+        let was_empty = {
+            let mut queue = self.queue.lock().map_err(|_| ExtEventError)?;
+            queue.push_back((selector.symbol(), payload, target));
+            queue.len() == 1
+        };
+        // Everything in the queue is handled together, so the runloop only needs waking
+        // for the first command of a batch.
+        if was_empty {
+            if let Some(handle) = self.handle.lock().unwrap().as_mut() {
+                handle.schedule_idle(EXT_EVENT_IDLE_TOKEN);
+            }
         }
         Ok(())
     }
+
+    /// Wake up the runloop of the running application, without submitting a [`Command`].
+    ///
+    /// This forces the application out of its wait for the next platform event and
+    /// calls [`AppDelegate::wake`] on the main thread, which can then check any state
+    /// it shares with the caller.
+    ///
+    /// Returns an error if the application hasn't been launched yet.
+    ///
+    /// [`Command`]: struct.Command.html
+    /// [`AppDelegate::wake`]: crate::AppDelegate::wake
+    pub fn wake(&self) -> Result<(), ExtEventError> {
+        match self.handle.lock().map_err(|_| ExtEventError)?.as_mut() {
+            Some(handle) => {
+                handle.schedule_idle(EXT_WAKE_IDLE_TOKEN);
+                Ok(())
+            }
+            None => Err(ExtEventError),
+        }
+    }
 }
 
 impl std::fmt::Display for ExtEventError {
@@ -192,17 +192,23 @@ pub use piet::{Color, ImageBuf, LinearGradient, RadialGradient, RenderContext, U
 pub use shell::image;
 pub use shell::keyboard_types;
 pub use shell::{
-    Application, Clipboard, ClipboardFormat, Code, Cursor, CursorDesc, Error as PlatformError,
-    FileInfo, FileSpec, FormatId, HotKey, KbKey, KeyEvent, Location, Modifiers, Monitor,
-    MouseButton, MouseButtons, RawMods, Region, Scalable, Scale, Screen, SysMods, TimerToken,
-    WindowHandle, WindowLevel, WindowState,
+    Application, Clipboard, ClipboardFormat, Code, Cursor, CursorDesc, CursorGrabMode,
+    Error as PlatformError, FileInfo, FileSpec, FormatId, FrameStats, Fullscreen, HotKey, Icon,
+    KbKey, KeyEvent, KeyRepeat, Location, Modifiers, Monitor, MouseButton, MouseButtons, RawMods,
+    Region, ResizeDirection, Scalable, Scale, Screen, SysMods, Theme, TimerToken, UrgencyLevel,
+    VideoMode, WindowHandle, WindowLevel, WindowState,
 };
 
+/// The physical position of a key, see [`KeyEvent::physical`].
+///
+/// This isn't called `KeyCode` here, because that name used to be an alias of [`KbKey`].
+pub use shell::KeyCode as PhysicalKeyCode;
+
 #[cfg(feature = "raw-win-handle")]
 pub use crate::shell::raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 pub use crate::core::{WidgetPod, WidgetState};
-pub use app::{AppLauncher, WindowConfig, WindowDesc, WindowSizePolicy};
+pub use app::{AppLauncher, EventLoopMode, WindowConfig, WindowDesc, WindowSizePolicy};
 pub use app_delegate::{AppDelegate, DelegateCtx};
 pub use box_constraints::BoxConstraints;
 pub use command::{sys as commands, Command, Notification, Selector, SingleUse, Target};
@@ -210,7 +216,9 @@ pub use contexts::{EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, UpdateCtx};
 pub use data::Data;
 pub use dialog::FileDialogOptions;
 pub use env::{Env, Key, KeyOrValue, Value, ValueType, ValueTypeError};
-pub use event::{Event, InternalEvent, InternalLifeCycle, LifeCycle};
+pub use event::{
+    Event, FileDropEvent, ImeComposition, InternalEvent, InternalLifeCycle, LifeCycle,
+};
 pub use ext_event::{ExtEventError, ExtEventSink};
 pub use lens::{Lens, LensExt};
 pub use localization::LocalizedString;
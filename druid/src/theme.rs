@@ -115,6 +115,8 @@ pub const SCROLLBAR_EDGE_WIDTH: Key<f64> =
 /// Minimum length for any scrollbar to be when measured on that
 /// scrollbar's primary axis.
 pub const SCROLLBAR_MIN_SIZE: Key<f64> = Key::new("org.linebender.theme.scrollbar_min_size");
+/// How far one line of mouse wheel scrolling moves, in display points.
+pub const SCROLL_LINE_HEIGHT: Key<f64> = Key::new("org.linebender.theme.scroll_line_height");
 
 /// An initial theme.
 pub(crate) fn add_to_env(env: Env) -> Env {
@@ -163,6 +165,7 @@ pub(crate) fn add_to_env(env: Env) -> Env {
         .adding(SCROLLBAR_MIN_SIZE, 45.)
         .adding(SCROLLBAR_RADIUS, 5.)
         .adding(SCROLLBAR_EDGE_WIDTH, 1.)
+        .adding(SCROLL_LINE_HEIGHT, druid_shell::SCROLL_LINE_DISTANCE)
         .adding(WIDGET_PADDING_VERTICAL, 10.0)
         .adding(WIDGET_PADDING_HORIZONTAL, 8.0)
         .adding(WIDGET_CONTROL_COMPONENT_PADDING, 4.0)
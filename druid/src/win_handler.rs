@@ -17,6 +17,7 @@
 use std::any::{Any, TypeId};
 use std::cell::{Ref, RefCell};
 use std::collections::{HashMap, VecDeque};
+use std::mem;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -27,20 +28,24 @@ use crate::shell::{
     Scale, TextFieldToken, WinHandler, WindowHandle,
 };
 
-use crate::app_delegate::{AppDelegate, DelegateCtx};
+use crate::app_delegate::{AppDelegate, DelegateCtx, WindowHandles};
 use crate::core::CommandQueue;
 use crate::ext_event::{ExtEventHost, ExtEventSink};
 use crate::menu::{ContextMenu, MenuItemId, MenuManager};
 use crate::window::{ImeUpdateFn, Window};
 use crate::{
-    Command, Data, Env, Event, Handled, InternalEvent, KeyEvent, PlatformError, Selector, Target,
-    TimerToken, WidgetId, WindowDesc, WindowId,
+    Command, Data, Env, Event, Handled, ImeComposition, InternalEvent, KeyEvent, PlatformError,
+    Selector, Target, TimerToken, Widget, WidgetId, WindowDesc, WindowId,
 };
 
 use crate::app::{PendingWindow, WindowConfig};
 use crate::command::sys as sys_cmd;
 use druid_shell::kurbo::Point;
-use druid_shell::{Modifiers, MouseButtons, WindowBuilder, WinitEvent};
+use druid_shell::text::Selection;
+use druid_shell::{
+    KbKey, KeyState, Modifiers, MouseButtons, WindowBuilder, WindowState, WinitEvent,
+};
+use winit::event::{Ime, TouchPhase};
 use winit::event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 
 pub(crate) const RUN_COMMANDS_TOKEN: IdleToken = IdleToken::new(1);
@@ -48,6 +53,11 @@ pub(crate) const RUN_COMMANDS_TOKEN: IdleToken = IdleToken::new(1);
 /// A token we are called back with if an external event was submitted.
 pub(crate) const EXT_EVENT_IDLE_TOKEN: IdleToken = IdleToken::new(2);
 
+/// A token we are called back with if an [`ExtEventSink`] asked to wake the runloop.
+///
+/// [`ExtEventSink`]: crate::ExtEventSink
+pub(crate) const EXT_WAKE_IDLE_TOKEN: IdleToken = IdleToken::new(3);
+
 /// The struct implements the druid-shell `WinHandler` trait.
 ///
 /// One `DruidHandler` exists per window.
@@ -95,7 +105,8 @@ pub(crate) struct InnerAppState<T> {
     ext_event_host: ExtEventHost,
     pub(crate) windows: Windows<T>,
     pub(crate) winit_windows: HashMap<winit::window::WindowId, WindowId>,
-    pub(crate) new_window: Option<WindowDesc<T>>,
+    /// Windows requested with `NEW_WINDOW`, waiting for the event loop to build them.
+    pub(crate) new_windows: VecDeque<WindowDesc<T>>,
     /// the application-level menu, only set on macos and only if there
     /// are no open windows.
     root_menu: Option<MenuManager<T>>,
@@ -107,6 +118,8 @@ pub(crate) struct InnerAppState<T> {
     pub(crate) data: T,
     ime_focus_change: Option<Box<dyn Fn()>>,
     pub(crate) event_proxy: Arc<EventLoopProxy<WinitEvent>>,
+    /// Set once [`Event::ApplicationQuit`] was sent, so it is only sent once.
+    quit_notified: bool,
 }
 
 /// All active windows.
@@ -150,6 +163,16 @@ impl<T> Windows<T> {
     }
 }
 
+impl<T> WindowHandles for Windows<T> {
+    fn ids(&self) -> Box<dyn Iterator<Item = WindowId> + '_> {
+        Box::new(self.windows.keys().copied())
+    }
+
+    fn handle(&self, id: WindowId) -> Option<&WindowHandle> {
+        self.windows.get(&id).map(|window| &window.handle)
+    }
+}
+
 impl<T> AppHandler<T> {
     pub(crate) fn new(app_state: AppState<T>) -> Self {
         Self { app_state }
@@ -171,7 +194,7 @@ impl<T> AppState<T> {
             command_queue: VecDeque::new(),
             file_dialogs: HashMap::new(),
             winit_windows: HashMap::new(),
-            new_window: None,
+            new_windows: VecDeque::new(),
             root_menu: None,
             menu_window: None,
             ext_event_host,
@@ -180,6 +203,7 @@ impl<T> AppState<T> {
             windows: Windows::default(),
             ime_focus_change: None,
             event_proxy,
+            quit_notified: false,
         }));
 
         AppState { inner }
@@ -224,12 +248,16 @@ impl<T: Data> InnerAppState<T> {
             ref mut data,
             ref ext_event_host,
             ref env,
+            ref winit_windows,
+            ref windows,
             ..
         } = self;
         let mut ctx = DelegateCtx {
             command_queue,
             app_data_type: TypeId::of::<T>(),
             ext_event_host,
+            winit_windows,
+            windows,
         };
         delegate
             .as_deref_mut()
@@ -250,6 +278,15 @@ impl<T: Data> InnerAppState<T> {
             .unwrap_or(Handled::No)
     }
 
+    fn delegate_wake(&mut self) {
+        self.with_delegate(|del, data, env, ctx| del.wake(data, env, ctx));
+    }
+
+    fn delegate_quit_requested(&mut self) -> Handled {
+        self.with_delegate(|del, data, env, ctx| del.quit_requested(data, env, ctx))
+            .unwrap_or(Handled::No)
+    }
+
     fn connect(&mut self, id: WindowId, handle: &WindowHandle) {
         self.windows
             .connect(id, handle.clone(), self.ext_event_host.make_sink());
@@ -310,11 +347,47 @@ impl<T: Data> InnerAppState<T> {
 
     /// triggered by a menu item or other command.
     ///
-    /// This doesn't close the window; it calls the close method on the platform
-    /// window handle; the platform should close the window, and then call
-    /// our handlers `destroy()` method, at which point we can do our cleanup.
+    /// The platform window closes once its handle is dropped, and winit has no
+    /// `destroy()` callback for us to wait for, so we do our cleanup right away.
+    ///
+    /// Windows owned by this one are closed along with it. They get
+    /// [`Event::WindowDisconnected`], but no [`Event::WindowCloseRequested`], so they can't
+    /// keep themselves open.
     fn request_close_window(&mut self, window_id: WindowId) {
-        self.windows.remove(window_id);
+        self.do_window_event(window_id, Event::WindowDisconnected);
+        self.winit_windows.retain(|_, id| *id != window_id);
+        self.remove_window(window_id);
+
+        // Popups and other owned windows go with their owner.
+        let owned: Vec<_> = self
+            .windows
+            .iter_mut()
+            .filter(|window| window.owner == Some(window_id))
+            .map(|window| window.id)
+            .collect();
+        for id in owned {
+            self.request_close_window(id);
+        }
+    }
+
+    /// Hides the windows owned by `owner` while it is minimized, and shows them again
+    /// once it isn't.
+    ///
+    /// Windows the application hid itself stay hidden.
+    fn follow_owner_state(&mut self, owner: WindowId, window_state: WindowState) {
+        let minimized = window_state == WindowState::Minimized;
+        for window in self.windows.iter_mut() {
+            if window.owner != Some(owner) {
+                continue;
+            }
+            if minimized && !window.hidden_with_owner && window.handle.is_visible() {
+                window.handle.hide();
+                window.hidden_with_owner = true;
+            } else if !minimized && window.hidden_with_owner {
+                window.handle.show();
+                window.hidden_with_owner = false;
+            }
+        }
     }
 
     /// Requests the platform to close all windows.
@@ -344,8 +417,10 @@ impl<T: Data> InnerAppState<T> {
     }
 
     fn paint(&mut self, window_id: WindowId) {
+        // Widgets may change the data in response to `AnimFrame`, so the frame
+        // should show the result of that.
+        self.prepare_paint(window_id);
         if let Some(win) = self.windows.get_mut(window_id) {
-            win.prepare_paint(&mut self.command_queue, &mut self.data, &self.env);
             win.do_paint(&mut self.command_queue, &self.data, &self.env);
         }
     }
@@ -364,22 +439,14 @@ impl<T: Data> InnerAppState<T> {
                     return Handled::Yes;
                 }
                 if let Some(w) = self.windows.get_mut(id) {
+                    // `request_close_window` sends `WindowDisconnected` if this isn't handled.
                     return if cmd.is(sys_cmd::CLOSE_WINDOW) {
-                        let handled = w.event(
+                        w.event(
                             &mut self.command_queue,
                             Event::WindowCloseRequested,
                             &mut self.data,
                             &self.env,
-                        );
-                        if !handled.is_handled() {
-                            w.event(
-                                &mut self.command_queue,
-                                Event::WindowDisconnected,
-                                &mut self.data,
-                                &self.env,
-                            );
-                        }
-                        handled
+                        )
                     } else {
                         w.event(
                             &mut self.command_queue,
@@ -434,7 +501,14 @@ impl<T: Data> InnerAppState<T> {
         };
 
         if let Some(win) = self.windows.get_mut(source_id) {
-            win.event(&mut self.command_queue, event, &mut self.data, &self.env)
+            let focus_event = win.tab_focus_event(&event);
+            let handled = win.event(&mut self.command_queue, event, &mut self.data, &self.env);
+            match focus_event {
+                Some(event) if !handled.is_handled() => {
+                    win.event(&mut self.command_queue, event, &mut self.data, &self.env)
+                }
+                _ => handled,
+            }
         } else {
             Handled::No
         }
@@ -466,9 +540,9 @@ impl<T: Data> InnerAppState<T> {
                 // we need to call this outside of the borrow, so we create a
                 // closure that takes the correct window handle. yes, it feels
                 // weird.
-                // let handle = window.handle.clone();
-                // let f = Box::new(move || handle.set_focused_text_field(focus_change));
-                // self.ime_focus_change = Some(f);
+                let handle = window.handle.clone();
+                let f = Box::new(move || handle.set_focused_text_field(focus_change));
+                self.ime_focus_change = Some(f);
             }
 
             #[cfg(not(target_os = "macos"))]
@@ -611,6 +685,69 @@ impl<T: Data> AppState<T> {
         None
     }
 
+    pub(crate) fn get_handle(&self, window_id: &winit::window::WindowId) -> Option<WindowHandle> {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow().windows.get(window_id) {
+                return Some(window.handle.clone());
+            }
+        }
+        None
+    }
+
+    /// Returns the size, in pixels, to lay a resized window out at; see
+    /// `Window::constrain_aspect_ratio`.
+    pub(crate) fn constrain_aspect_ratio(
+        &self,
+        window_id: &winit::window::WindowId,
+        size: Size,
+    ) -> Size {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow_mut().windows.get_mut(window_id) {
+                return window.constrain_aspect_ratio(size);
+            }
+        }
+        size
+    }
+
+    /// Sends [`Event::WindowState`] to the window if its state changed.
+    pub(crate) fn check_window_state(&mut self, window_id: &winit::window::WindowId) {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            let window_state = self
+                .inner
+                .borrow_mut()
+                .windows
+                .get_mut(window_id)
+                .and_then(|window| window.window_state_changed());
+            if let Some(window_state) = window_state {
+                self.do_window_event(Event::WindowState(window_state), window_id);
+                self.inner
+                    .borrow_mut()
+                    .follow_owner_state(window_id, window_state);
+            }
+        }
+    }
+
     pub(crate) fn get_scale(&self, window_id: &winit::window::WindowId) -> Option<f64> {
         let window_id = {
             self.inner
@@ -627,6 +764,23 @@ impl<T: Data> AppState<T> {
         None
     }
 
+    /// Returns `true` if the window uses a fixed scale instead of the platform's.
+    pub(crate) fn has_scale_override(&self, window_id: &winit::window::WindowId) -> bool {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow().windows.get(window_id) {
+                return window.scale_override.is_some();
+            }
+        }
+        false
+    }
+
     pub(crate) fn set_mods(&self, window_id: &winit::window::WindowId, mods: Modifiers) {
         let window_id = {
             self.inner
@@ -638,6 +792,7 @@ impl<T: Data> AppState<T> {
         if let Some(window_id) = window_id {
             if let Some(window) = self.inner.borrow_mut().windows.get_mut(window_id) {
                 window.mods = mods;
+                window.handle.record_modifiers(mods);
             }
         }
     }
@@ -658,10 +813,247 @@ impl<T: Data> AppState<T> {
         None
     }
 
+    /// If the key press matches a hotkey of the window's menu, handle it as that menu
+    /// item being selected and return `true`.
+    pub(crate) fn handle_menu_hotkey(
+        &mut self,
+        event: &KeyEvent,
+        window_id: &winit::window::WindowId,
+    ) -> bool {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            let cmd_id = self
+                .inner
+                .borrow()
+                .windows
+                .get(window_id)
+                .filter(|window| window.handle.is_enabled())
+                .and_then(|window| window.handle.menu_command_for_key(event));
+            if let Some(cmd_id) = cmd_id {
+                self.handle_system_cmd(cmd_id, Some(window_id));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Adds a pinch delta to the window's current gesture, returning its zoom factor.
+    pub(crate) fn zoom_gesture(
+        &self,
+        window_id: &winit::window::WindowId,
+        delta: f64,
+        phase: TouchPhase,
+    ) -> Option<f64> {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow_mut().windows.get_mut(window_id) {
+                return window.zoom_gesture(delta, phase);
+            }
+        }
+        None
+    }
+
+    pub(crate) fn suppresses_key_repeat(&self, window_id: &winit::window::WindowId) -> bool {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow().windows.get(window_id) {
+                return window.suppress_key_repeat;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn is_composing(&self, window_id: &winit::window::WindowId) -> bool {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        if let Some(window_id) = window_id {
+            if let Some(window) = self.inner.borrow().windows.get(window_id) {
+                return window.ime_composing;
+            }
+        }
+        false
+    }
+
+    /// Apply a platform IME event to the focused text field of the window.
+    pub(crate) fn do_winit_ime_event(&mut self, ime: Ime, window_id: &winit::window::WindowId) {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        let window_id = match window_id {
+            Some(window_id) => window_id,
+            None => return,
+        };
+        if let Some(window) = self.inner.borrow_mut().windows.get_mut(window_id) {
+            // Some backends send `Enabled` but still type plain keys without a `Commit`.
+            if matches!(ime, Ime::Preedit(..) | Ime::Commit(_)) {
+                window.ime_seen = true;
+            }
+            // Like other input, IME text doesn't reach a disabled window.
+            if !window.handle.is_enabled() {
+                return;
+            }
+        }
+        self.apply_ime_event(ime, window_id);
+    }
+
+    /// Type the text of a key press into the focused text field, for platforms that
+    /// don't send IME events.
+    pub(crate) fn type_key_text(&mut self, event: &KeyEvent, window_id: &winit::window::WindowId) {
+        let window_id = {
+            self.inner
+                .borrow()
+                .winit_windows
+                .get(window_id)
+                .map(|w| w.clone())
+        };
+        let window_id = match window_id {
+            Some(window_id) => window_id,
+            None => return,
+        };
+        let types_text = self
+            .inner
+            .borrow()
+            .windows
+            .get(window_id)
+            .map_or(false, |window| {
+                !window.ime_seen
+                    && window.handle.is_enabled()
+                    && window.focused_text_field().is_some()
+            });
+        let mods = event.mods;
+        if !types_text || event.state != KeyState::Down || mods.ctrl() || mods.alt() || mods.meta()
+        {
+            return;
+        }
+        if let Some(text) = event.text() {
+            self.apply_ime_event(Ime::Commit(text.to_string()), window_id);
+        }
+    }
+
+    fn apply_ime_event(&mut self, ime: Ime, window_id: WindowId) {
+        let (token, was_composing) = {
+            let mut inner = self.inner.borrow_mut();
+            let window = match inner.windows.get_mut(window_id) {
+                Some(window) => window,
+                None => return,
+            };
+            let was_composing = window.ime_composing;
+            window.ime_composing = matches!(&ime, Ime::Preedit(text, _) if !text.is_empty());
+            (window.focused_text_field(), was_composing)
+        };
+        let composition = match &ime {
+            Ime::Preedit(text, cursor) => Some(ImeComposition {
+                text: text.clone(),
+                cursor: cursor.map(|(start, end)| start..end),
+            }),
+            Ime::Commit(_) | Ime::Disabled if was_composing => Some(ImeComposition {
+                text: String::new(),
+                cursor: None,
+            }),
+            _ => None,
+        };
+        if let Some(composition) = composition {
+            self.do_window_event(Event::ImeComposition(composition), window_id);
+        }
+        let (token, ime) = match (token, ime) {
+            (Some(token), ime) => (token, ime),
+            // Without a text field, committed text goes to the focused widget as a key press.
+            (None, Ime::Commit(text)) if !text.is_empty() => {
+                let mut key_event = KeyEvent::default();
+                key_event.state = KeyState::Down;
+                key_event.key = KbKey::Character(text);
+                self.do_window_event(Event::KeyDown(key_event), window_id);
+                return;
+            }
+            (None, _) => return,
+        };
+
+        let (text, cursor, is_commit) = match ime {
+            Ime::Preedit(text, cursor) => (text, cursor, false),
+            Ime::Commit(text) => (text, None, true),
+            Ime::Disabled => (String::new(), None, false),
+            Ime::Enabled => return,
+        };
+
+        let mut input_handler = self.inner.borrow_mut().get_ime_lock(window_id, token, true);
+        let composition = input_handler.composition_range();
+        if composition.is_none() && text.is_empty() {
+            // nothing is being composed, and there is nothing to insert.
+            std::mem::drop(input_handler);
+            self.release_ime_lock(window_id, token);
+            return;
+        }
+        let range = composition.unwrap_or_else(|| input_handler.selection().range());
+        input_handler.replace_range(range.clone(), &text);
+        if !is_commit && !text.is_empty() {
+            // A preedit stays a composition, even when the IME hides its caret.
+            let end = range.start + text.len();
+            input_handler.set_composition_range(Some(range.start..end));
+            let selection = match cursor {
+                Some((anchor, active)) => {
+                    Selection::new(range.start + anchor, range.start + active)
+                }
+                None => Selection::caret(end),
+            };
+            input_handler.set_selection(selection);
+        } else {
+            input_handler.set_composition_range(None);
+            let caret = if is_commit {
+                range.start + text.len()
+            } else {
+                range.start
+            };
+            input_handler.set_selection(Selection::caret(caret));
+        }
+        std::mem::drop(input_handler);
+        self.release_ime_lock(window_id, token);
+
+        if let Some(window) = self.inner.borrow().windows.get(window_id) {
+            window.update_ime_cursor_area();
+        }
+    }
+
     pub(crate) fn create_new_windows(&self, window_target: &EventLoopWindowTarget<WinitEvent>) {
-        let desc = { self.inner.borrow_mut().new_window.take() };
-        if let Some(desc) = desc {
-            desc.build_native(&self, window_target);
+        loop {
+            let desc = self.inner.borrow_mut().new_windows.pop_front();
+            match desc {
+                Some(desc) => {
+                    let visible = desc.starts_visible();
+                    match desc.build_native(self, window_target) {
+                        Ok(window) if visible => window.show(),
+                        Ok(_) => (),
+                        Err(e) => tracing::error!("failed to create window: {}", e),
+                    }
+                }
+                None => break,
+            }
         }
     }
 
@@ -669,7 +1061,7 @@ impl<T: Data> AppState<T> {
         &mut self,
         event: Event,
         window_id: &winit::window::WindowId,
-    ) {
+    ) -> Handled {
         let window_id = {
             self.inner
                 .borrow()
@@ -678,8 +1070,18 @@ impl<T: Data> AppState<T> {
                 .map(|w| w.clone())
         };
         if let Some(window_id) = window_id {
-            self.do_window_event(event, window_id);
+            // Not every platform can disable a window, so we also drop its input here.
+            let enabled = self
+                .inner
+                .borrow()
+                .windows
+                .get(window_id)
+                .map_or(true, |window| window.handle.is_enabled());
+            if enabled || !event.is_user_input() {
+                return self.do_window_event(event, window_id);
+            }
         }
+        Handled::No
     }
 
     /// Send an event to the widget hierarchy.
@@ -691,14 +1093,39 @@ impl<T: Data> AppState<T> {
     pub(crate) fn do_window_event(&mut self, event: Event, window_id: WindowId) -> Handled {
         let result = self.inner.borrow_mut().do_window_event(window_id, event);
         self.process_commands();
+        self.focus_requested_text_fields();
         self.inner.borrow_mut().do_update();
         let ime_change = self.inner.borrow_mut().ime_focus_change.take();
         if let Some(ime_change) = ime_change {
-            (ime_change)()
+            (ime_change)();
+            self.update_ime_cursor_areas();
         }
         result
     }
 
+    /// Gives focus to the widgets whose text fields were focused with
+    /// [`WindowHandle::focus_text_field`].
+    fn focus_requested_text_fields(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        let inner = &mut *inner;
+        for window in inner.windows.iter_mut() {
+            // Blurring only disables the IME; the widget keeps its focus.
+            if let Some(Some(token)) = window.handle.take_text_focus_request() {
+                window.focus_text_field(token, &mut inner.command_queue, &inner.data, &inner.env);
+            }
+        }
+    }
+
+    /// Moves the IME cursor area of every window whose focused text field changed, or
+    /// had its caret moved by the application.
+    fn update_ime_cursor_areas(&self) {
+        for window in self.inner.borrow().windows.windows.values() {
+            if window.handle.take_moved_text_field().is_some() {
+                window.update_ime_cursor_area();
+            }
+        }
+    }
+
     fn prepare_paint_window(&mut self, window_id: WindowId) {
         self.inner.borrow_mut().prepare_paint(window_id);
     }
@@ -731,6 +1158,11 @@ impl<T: Data> AppState<T> {
                 self.process_commands();
                 self.inner.borrow_mut().do_update();
             }
+            EXT_WAKE_IDLE_TOKEN => {
+                self.inner.borrow_mut().delegate_wake();
+                self.process_commands();
+                self.inner.borrow_mut().do_update();
+            }
             other => tracing::warn!("unexpected idle token {:?}", other),
         }
     }
@@ -775,7 +1207,11 @@ impl<T: Data> AppState<T> {
         use Target as T;
         match cmd.target() {
             // these are handled the same no matter where they come from
-            _ if cmd.is(sys_cmd::QUIT_APP) => self.quit(),
+            _ if cmd.is(sys_cmd::QUIT_APP) => {
+                if self.request_quit() {
+                    self.quit();
+                }
+            }
             #[cfg(target_os = "macos")]
             _ if cmd.is(sys_cmd::HIDE_APPLICATION) => self.hide_app(),
             #[cfg(target_os = "macos")]
@@ -803,6 +1239,9 @@ impl<T: Data> AppState<T> {
                 }
             }
             T::Window(id) if cmd.is(sys_cmd::SHOW_WINDOW) => self.show_window(id),
+            T::Window(id) if cmd.is(sys_cmd::REPLACE_ROOT_WIDGET) => {
+                self.replace_root_widget(cmd, id)
+            }
             T::Window(id) if cmd.is(sys_cmd::PASTE) => self.do_paste(id),
             _ if cmd.is(sys_cmd::CLOSE_WINDOW) => {
                 tracing::warn!("CLOSE_WINDOW command must target a window.")
@@ -893,7 +1332,7 @@ impl<T: Data> AppState<T> {
         // // The NEW_WINDOW command is private and only druid can receive it by normal means,
         // // thus unwrapping can be considered safe and deserves a panic.
         let desc = desc.take().unwrap().downcast::<WindowDesc<T>>().unwrap();
-        self.inner.borrow_mut().new_window = Some(*desc);
+        self.inner.borrow_mut().new_windows.push_back(*desc);
         self.inner
             .borrow()
             .event_proxy
@@ -930,20 +1369,69 @@ impl<T: Data> AppState<T> {
         self.inner.borrow().windows.count()
     }
 
-    pub(crate) fn request_close_wint_window(&mut self, id: &winit::window::WindowId) {
-        let window_id = {
-            self.inner
-                .borrow_mut()
-                .winit_windows
-                .remove(id)
-                .map(|w| w.clone())
-        };
+    /// Asks the delegate whether the application may quit.
+    ///
+    /// Returns `true` if the application should quit.
+    pub(crate) fn request_quit(&mut self) -> bool {
+        let handled = self.inner.borrow_mut().delegate_quit_requested();
+        self.process_commands();
+        self.inner.borrow_mut().do_update();
+        !handled.is_handled()
+    }
+
+    /// Sends [`Event::ApplicationQuit`] to every open window, right before the event
+    /// loop exits.
+    ///
+    /// This does nothing if they were told already.
+    pub(crate) fn notify_quit(&mut self) {
+        if mem::replace(&mut self.inner.borrow_mut().quit_notified, true) {
+            return;
+        }
+        let window_ids: Vec<WindowId> = self
+            .inner
+            .borrow()
+            .windows
+            .windows
+            .keys()
+            .copied()
+            .collect();
+        for window_id in window_ids {
+            self.do_window_event(Event::ApplicationQuit, window_id);
+        }
+    }
+
+    /// Called when the user asks to close a window, e.g. with the titlebar "X".
+    ///
+    /// The window is only closed if neither the delegate nor a widget handles the
+    /// resulting [`Event::WindowCloseRequested`].
+    ///
+    /// If closing the window would quit the application, the delegate is asked first, and
+    /// the window stays open if it refuses. Returns `true` if the application should quit.
+    pub(crate) fn request_close_wint_window(
+        &mut self,
+        id: &winit::window::WindowId,
+        quits: bool,
+    ) -> bool {
+        let window_id = { self.inner.borrow().winit_windows.get(id).map(|w| w.clone()) };
+        let mut quit = false;
         if let Some(window_id) = window_id {
-            self.inner
+            let handled = self
+                .inner
                 .borrow_mut()
                 .do_window_event(window_id, Event::WindowCloseRequested);
-            self.inner.borrow_mut().request_close_window(window_id);
+            if !handled.is_handled() && (!quits || self.request_quit()) {
+                quit = quits;
+                if quit {
+                    // While the window is still around to hear about it.
+                    self.notify_quit();
+                }
+                self.inner.borrow_mut().request_close_window(window_id);
+            }
+            // e.g. a "save changes?" dialog, or closing the window after all
+            self.process_commands();
+            self.inner.borrow_mut().do_update();
         }
+        quit
     }
 
     fn request_close_all_windows(&mut self) {
@@ -960,6 +1448,31 @@ impl<T: Data> AppState<T> {
         }
     }
 
+    fn replace_root_widget(&mut self, cmd: Command, id: WindowId) {
+        let root = cmd
+            .get_unchecked(sys_cmd::REPLACE_ROOT_WIDGET)
+            .take()
+            .and_then(|root| root.downcast::<Box<dyn Widget<T>>>().ok());
+        // The payload is gone if the command already reached another handler.
+        let root = match root {
+            Some(root) => root,
+            None => {
+                tracing::error!(
+                    "{} command must carry a root widget for the application state.",
+                    sys_cmd::REPLACE_ROOT_WIDGET
+                );
+                return;
+            }
+        };
+        let mut inner = self.inner.borrow_mut();
+        if let Some(window) = inner.windows.get_mut(id) {
+            window.replace_root(*root);
+            // The new widgets are added and get to see the window connected, as if the
+            // window had just been opened.
+            inner.do_window_event(id, Event::WindowConnected);
+        }
+    }
+
     fn do_paste(&mut self, window_id: WindowId) {
         let event = Event::Paste(self.inner.borrow().app.clipboard());
         self.inner.borrow_mut().do_window_event(window_id, event);
@@ -971,6 +1484,7 @@ impl<T: Data> AppState<T> {
         if let Some(func) = update_fn {
             func(params.event);
         }
+        self.update_ime_cursor_areas();
     }
 
     fn release_ime_lock(&mut self, window_id: WindowId, token: TextFieldToken) {
@@ -1006,11 +1520,32 @@ impl<T: Data> AppState<T> {
     ) -> Result<WindowHandle, PlatformError> {
         let builder = WindowBuilder::new(self.app());
         let builder = config.apply_to_builder(builder);
+        let owner = config.owner.and_then(|owner| {
+            self.inner
+                .borrow()
+                .windows
+                .get(owner)
+                .map(|w| w.handle.clone())
+        });
+        let builder = match owner {
+            Some(owner) => builder.set_owner(&owner),
+            None => builder,
+        };
 
         let data = self.data();
         let env = self.env();
 
         pending.size_policy = config.size_policy;
+        pending.aspect_ratio = config.aspect_ratio;
+        pending.background = config.background.clone();
+        pending.scale_override = if config.physical_pixels == Some(true) {
+            Some(1.0)
+        } else {
+            config.scale_factor
+        };
+        pending.zoom_sensitivity = config.zoom_sensitivity.unwrap_or(1.0);
+        pending.owner = config.owner;
+        pending.suppress_key_repeat = config.suppress_key_repeat.unwrap_or(false);
         pending.title.resolve(&data, &env);
         let mut builder = builder.set_title(pending.title.display_text().to_string());
 
@@ -1067,8 +1602,9 @@ impl<T: Data> WinHandler for DruidHandler<T> {
         self.app_state.do_window_event(event, self.window_id);
     }
 
-    fn scale(&mut self, _scale: Scale) {
-        // TODO: Do something with the scale
+    fn scale(&mut self, scale: Scale) {
+        let event = Event::WindowScale(scale);
+        self.app_state.do_window_event(event, self.window_id);
     }
 
     fn command(&mut self, id: u32) {
@@ -15,18 +15,21 @@
 //! Management of multiple windows.
 
 use druid_shell::piet::WgpuRenderer;
-use druid_shell::{Modifiers, MouseButtons};
+use druid_shell::{KbKey, Modifiers, MouseButtons, WindowState};
 use std::cell::RefCell;
 use std::collections::{HashMap, VecDeque};
 use std::mem;
 use std::rc::Rc;
 use tracing::{error, info, info_span};
+use winit::event::TouchPhase;
 
 // Automatically defaults to std::time::Instant on non Wasm platforms
 use instant::Instant;
 
 use crate::piet::{Color, Piet, RenderContext};
-use crate::shell::{text::InputHandler, Counter, Cursor, Region, TextFieldToken, WindowHandle};
+use crate::shell::{
+    text::InputHandler, Counter, Cursor, Region, Scale, TextFieldToken, WindowHandle,
+};
 
 use crate::app::{PendingWindow, WindowSizePolicy};
 use crate::contexts::ContextState;
@@ -38,7 +41,7 @@ use crate::util::ExtendDrain;
 use crate::widget::LabelText;
 use crate::win_handler::RUN_COMMANDS_TOKEN;
 use crate::{
-    BoxConstraints, Data, Env, Event, EventCtx, ExtEventSink, Handled, InternalEvent,
+    BoxConstraints, Data, Env, Event, EventCtx, ExtEventSink, Handled, Insets, InternalEvent,
     InternalLifeCycle, LayoutCtx, LifeCycle, LifeCycleCtx, Menu, PaintCtx, Point, Size, TimerToken,
     UpdateCtx, Widget, WidgetId, WidgetPod,
 };
@@ -74,7 +77,27 @@ pub struct Window<T> {
     pub(crate) ime_handlers: Vec<(TextFieldToken, TextFieldRegistration)>,
     ext_handle: ExtEventSink,
     pub(crate) ime_focus_change: Option<Option<TextFieldToken>>,
+    /// `true` while the platform IME has an active composition.
+    pub(crate) ime_composing: bool,
+    /// Set once the platform sent text through the IME. Until then, text typed into the
+    /// focused text field is taken from key presses.
+    pub(crate) ime_seen: bool,
+    aspect_ratio: Option<f64>,
+    /// The size, in pixels, we last asked the platform for to restore the aspect ratio.
+    aspect_correction: Option<Size>,
     pub(crate) mods: Modifiers,
+    /// The state last reported with [`Event::WindowState`].
+    window_state: WindowState,
+    background: Option<Color>,
+    /// The scale factor used instead of the platform's, if any.
+    pub(crate) scale_override: Option<f64>,
+    zoom: PinchZoom,
+    /// The window this one is closed and minimized with, if any.
+    pub(crate) owner: Option<WindowId>,
+    /// Set while the window is hidden because its owner is minimized.
+    pub(crate) hidden_with_owner: bool,
+    /// Whether key repeats are dropped before they reach the widgets.
+    pub(crate) suppress_key_repeat: bool,
 }
 
 impl<T> Window<T> {
@@ -88,12 +111,13 @@ impl<T> Window<T> {
         size.width = size.width.max(1.0);
         size.height = size.height.max(1.0);
 
-        let scale = handle.get_scale();
+        let scale = pending.scale_override.unwrap_or_else(|| handle.get_scale());
+        let window_state = handle.get_window_state();
         let mut renderer = WgpuRenderer::new(&handle).unwrap();
         renderer.set_size(size);
         renderer.set_scale(scale);
 
-        let size = size / scale;
+        let size = Scale::new(scale, scale).to_dp(size);
         Window {
             id,
             root: WidgetPod::new(pending.root),
@@ -118,6 +142,83 @@ impl<T> Window<T> {
             ext_handle,
             ime_handlers: Vec::new(),
             ime_focus_change: None,
+            ime_composing: false,
+            ime_seen: false,
+            aspect_ratio: pending.aspect_ratio,
+            aspect_correction: None,
+            window_state,
+            background: pending.background,
+            scale_override: pending.scale_override,
+            zoom: PinchZoom::new(pending.zoom_sensitivity),
+            owner: pending.owner,
+            hidden_with_owner: false,
+            suppress_key_repeat: pending.suppress_key_repeat,
+        }
+    }
+
+    /// Adds a pinch delta to the current gesture, returning the zoom factor for
+    /// [`Event::ZoomFactor`], or `None` if the gesture was cancelled.
+    pub(crate) fn zoom_gesture(&mut self, delta: f64, phase: TouchPhase) -> Option<f64> {
+        self.zoom.update(delta, phase)
+    }
+
+    /// The scale the widgets are laid out and painted at.
+    fn scale(&self) -> Scale {
+        Scale::new(self.scale, self.scale)
+    }
+
+    /// How many units of widget space make one of the platform's display points.
+    ///
+    /// This is 1.0 unless the scale is overridden, e.g. to use physical pixels.
+    fn units_per_point(&self) -> f64 {
+        self.handle.get_scale() / self.scale
+    }
+
+    /// Returns the window's new state if it changed since the last call.
+    pub(crate) fn window_state_changed(&mut self) -> Option<WindowState> {
+        let window_state = self.handle.get_window_state();
+        if window_state == self.window_state {
+            return None;
+        }
+        self.window_state = window_state;
+        Some(window_state)
+    }
+}
+
+/// The zoom factor of the pinch gesture in progress.
+#[derive(Debug)]
+struct PinchZoom {
+    sensitivity: f64,
+    factor: f64,
+}
+
+impl PinchZoom {
+    fn new(sensitivity: f64) -> PinchZoom {
+        PinchZoom {
+            sensitivity,
+            factor: 1.0,
+        }
+    }
+
+    /// Adds a pinch delta, returning the zoom factor of the gesture so far, or `None` if
+    /// it was cancelled.
+    fn update(&mut self, delta: f64, phase: TouchPhase) -> Option<f64> {
+        if phase == TouchPhase::Started {
+            self.factor = 1.0;
+        }
+        // Multiplying keeps zooming in and back out by the same deltas at the same zoom.
+        self.factor *= (delta * self.sensitivity).exp();
+        let factor = self.factor;
+        match phase {
+            TouchPhase::Started | TouchPhase::Moved => Some(factor),
+            TouchPhase::Ended => {
+                self.factor = 1.0;
+                Some(factor)
+            }
+            TouchPhase::Cancelled => {
+                self.factor = 1.0;
+                None
+            }
         }
     }
 }
@@ -262,6 +363,13 @@ impl<T: Data> Window<T> {
         data: &mut T,
         env: &Env,
     ) -> Handled {
+        // With a fixed scale factor, the widgets never hear about the platform's.
+        let event = match (event, self.scale_override) {
+            (Event::WindowSize(size, Some(_)), Some(scale)) => Event::WindowSize(size, Some(scale)),
+            (Event::WindowScale(_), Some(scale)) => Event::WindowScale(Scale::new(scale, scale)),
+            (event, _) => event,
+        };
+
         match &event {
             Event::WindowSize(size, new_scale) => {
                 if let Some(scale) = new_scale {
@@ -272,14 +380,23 @@ impl<T: Data> Window<T> {
                 size.width = size.width.max(1.0);
                 size.height = size.height.max(1.0);
 
-                self.size = size / self.scale;
+                self.size = self.scale().to_dp(size);
                 self.renderer.borrow_mut().set_size(size);
             }
+            Event::WindowScale(scale) => {
+                self.scale = scale.x();
+                self.renderer.borrow_mut().set_scale(scale.x());
+            }
             Event::MouseDown(e) | Event::MouseUp(e) | Event::MouseMove(e) | Event::Wheel(e) => {
                 self.last_mouse_pos = Some(e.pos);
                 self.last_mouse_buttons = Some(e.buttons);
+                self.handle.record_cursor_position(self.last_mouse_pos);
+                self.handle.record_mouse_buttons(e.buttons);
+            }
+            Event::Internal(InternalEvent::MouseLeave) => {
+                self.last_mouse_pos = None;
+                self.handle.record_cursor_position(None);
             }
-            Event::Internal(InternalEvent::MouseLeave) => self.last_mouse_pos = None,
             _ => (),
         }
 
@@ -339,6 +456,15 @@ impl<T: Data> Window<T> {
             Handled::from(ctx.is_handled)
         };
 
+        // Containers get to move the focus first, otherwise it follows the focus chain.
+        if !is_handled.is_handled() && widget_state.request_focus.is_none() {
+            match event {
+                Event::FocusNext => widget_state.request_focus = Some(FocusChange::Next),
+                Event::FocusPrev => widget_state.request_focus = Some(FocusChange::Previous),
+                _ => (),
+            }
+        }
+
         // Clean up the timer token and do it immediately after the event handling
         // because the token may be reused and re-added in a lifecycle pass below.
         if let Event::Internal(InternalEvent::RouteTimer(token, _)) = event {
@@ -431,16 +557,28 @@ impl<T: Data> Window<T> {
         self.post_event_processing(&mut widget_state, queue, data, env, false);
     }
 
+    /// Replaces the root widget, dropping the state of the old widget tree.
+    ///
+    /// The new root doesn't get [`LifeCycle::WidgetAdded`] until the next event.
+    pub(crate) fn replace_root(&mut self, root: Box<dyn Widget<T>>) {
+        for (token, _) in self.ime_handlers.drain(..) {
+            self.handle.remove_text_field(token);
+        }
+        self.timers.clear();
+        self.root = WidgetPod::new(root);
+        self.focus = None;
+        self.last_focus = None;
+        self.needs_layout = true;
+        self.handle.invalidate();
+    }
+
     pub(crate) fn invalidate_and_finalize(&mut self) {
         if self.needs_layout {
             self.handle.invalidate();
         } else {
-            if !self.invalid.is_empty() {
-                self.handle.invalidate();
+            for rect in self.invalid.rects() {
+                self.handle.invalidate_rect(*rect);
             }
-            // for rect in self.invalid.rects() {
-            //     self.handle.invalidate_rect(*rect);
-            // }
         }
     }
 
@@ -471,10 +609,12 @@ impl<T: Data> Window<T> {
     }
 
     pub(crate) fn do_paint(&mut self, queue: &mut CommandQueue, data: &T, env: &Env) {
+        let layout_start = Instant::now();
         if self.needs_layout {
             self.needs_layout = false;
             self.layout(queue, data, env);
         }
+        let paint_start = Instant::now();
 
         // for &r in invalid.rects() {
         //     piet.clear(
@@ -486,11 +626,17 @@ impl<T: Data> Window<T> {
         //         },
         //     );
         // }
+        // The surface doesn't keep the last frame, so everything is painted every time,
+        // whatever was invalidated. The region is only taken so that the next invalidation
+        // requests a new redraw.
+        self.handle.take_invalid_region();
         self.invalid.clear();
         self.invalid.add_rect(self.size.to_rect());
         let invalid = self.invalid.clone();
         self.paint(&invalid, queue, data, env);
         self.invalid.clear();
+        self.handle
+            .record_frame(paint_start - layout_start, paint_start.elapsed());
     }
 
     fn layout(&mut self, queue: &mut CommandQueue, data: &T, env: &Env) {
@@ -508,9 +654,18 @@ impl<T: Data> Window<T> {
             widget_state: &mut widget_state,
             mouse_pos: self.last_mouse_pos,
         };
+        // The handle deals in the platform's display points, the widgets in their own units.
+        let per_point = self.units_per_point();
         let bc = match self.size_policy {
             WindowSizePolicy::User => BoxConstraints::tight(self.size),
-            WindowSizePolicy::Content => BoxConstraints::UNBOUNDED,
+            // The content decides the size, but only within the window's limits.
+            WindowSizePolicy::Content => BoxConstraints::new(
+                self.handle.get_min_size().unwrap_or(Size::ZERO) * per_point,
+                self.handle
+                    .get_max_size()
+                    .unwrap_or(Size::new(f64::INFINITY, f64::INFINITY))
+                    * per_point,
+            ),
         };
 
         let content_size = {
@@ -520,11 +675,19 @@ impl<T: Data> Window<T> {
         };
 
         if let WindowSizePolicy::Content = self.size_policy {
+            // Keep to the window's limits even if the root widget doesn't.
+            let content_size = bc.constrain(content_size);
             let insets = self.handle.content_insets();
+            let insets = Insets::new(
+                insets.x0 * per_point,
+                insets.y0 * per_point,
+                insets.x1 * per_point,
+                insets.y1 * per_point,
+            );
             let full_size = (content_size.to_rect() + insets).size();
             if self.size != full_size {
                 self.size = full_size;
-                self.handle.set_size(full_size)
+                self.handle.set_size(full_size / per_point)
             }
         }
         self.root
@@ -571,6 +734,10 @@ impl<T: Data> Window<T> {
 
         let start = std::time::SystemTime::now();
 
+        if let Some(background) = self.background.as_ref().filter(|_| !self.transparent) {
+            ctx.render_ctx.fill(self.size.to_rect(), background);
+        }
+
         let root = &mut self.root;
         info_span!("paint").in_scope(|| {
             ctx.with_child_ctx(invalid.clone(), |ctx| root.paint_raw(ctx, data, env));
@@ -641,6 +808,85 @@ impl<T: Data> Window<T> {
             .unwrap()
     }
 
+    /// The text field belonging to the focused widget, if it registered one.
+    pub(crate) fn focused_text_field(&self) -> Option<TextFieldToken> {
+        self.ime_handlers
+            .iter()
+            .find(|(_, reg)| Some(reg.widget_id) == self.focus)
+            .map(|(token, _)| *token)
+    }
+
+    /// Gives focus to the widget that registered the text field `token`.
+    pub(crate) fn focus_text_field(
+        &mut self,
+        token: TextFieldToken,
+        queue: &mut CommandQueue,
+        data: &T,
+        env: &Env,
+    ) {
+        let widget_id = match self.ime_handlers.iter().find(|(t, _)| *t == token) {
+            Some((_, reg)) => reg.widget_id,
+            None => return,
+        };
+        let mut widget_state = WidgetState::new(self.root.id(), Some(self.size));
+        widget_state.request_focus = Some(FocusChange::Focus(widget_id));
+        self.post_event_processing(&mut widget_state, queue, data, env, false);
+    }
+
+    /// Given the new size of the window in pixels, asks the platform to resize it back to the
+    /// locked aspect ratio if necessary, and returns the size to lay out at in the meantime.
+    ///
+    /// To avoid fighting the platform, e.g. when the corrected size violates the minimum size,
+    /// the size following a correction is always accepted as is.
+    pub(crate) fn constrain_aspect_ratio(&mut self, size: Size) -> Size {
+        let ratio = match self.aspect_ratio {
+            Some(ratio) if ratio > 0.0 && ratio.is_finite() => ratio,
+            _ => return size,
+        };
+        if self.aspect_correction.take().is_some() {
+            return size;
+        }
+
+        // Follow whichever edge the user moved the most.
+        let old = self.scale().to_px(self.size);
+        let corrected = if (size.width - old.width).abs() >= (size.height - old.height).abs() {
+            Size::new(size.width, (size.width / ratio).round())
+        } else {
+            Size::new((size.height * ratio).round(), size.height)
+        };
+        if (corrected.width - size.width).abs() <= 1.0
+            && (corrected.height - size.height).abs() <= 1.0
+        {
+            return size;
+        }
+        self.aspect_correction = Some(corrected);
+        // The handle takes the platform's display points, which a fixed scale factor
+        // doesn't change.
+        self.handle.set_size(self.handle.scale().to_dp(corrected));
+        corrected
+    }
+
+    /// Tell the platform where the caret of the focused text field is, so that
+    /// IME candidate windows can be placed next to it.
+    pub(crate) fn update_ime_cursor_area(&self) {
+        let reg = match self
+            .ime_handlers
+            .iter()
+            .find(|(_, reg)| Some(reg.widget_id) == self.focus)
+        {
+            Some((_, reg)) => reg,
+            None => return,
+        };
+        let rect = reg.document.acquire(false).and_then(|handler| {
+            let selection = handler.selection();
+            handler.slice_bounding_box(selection.range())
+        });
+        reg.document.release();
+        if let Some(rect) = rect {
+            self.handle.set_ime_cursor_area(rect.origin(), rect.size());
+        }
+    }
+
     fn update_focus(
         &mut self,
         widget_state: &mut WidgetState,
@@ -691,16 +937,15 @@ impl<T: Data> Window<T> {
     /// This will be called from outside the main app state in order to avoid
     /// reentrancy problems.
     pub(crate) fn ime_invalidation_fn(&self, widget: WidgetId) -> Option<Box<ImeUpdateFn>> {
-        None
-        // let token = self
-        //     .ime_handlers
-        //     .iter()
-        //     .find(|(_, reg)| reg.widget_id == widget)
-        //     .map(|(t, _)| *t)?;
-        // let window_handle = self.handle.clone();
-        // Some(Box::new(move |event| {
-        //     window_handle.update_text_field(token, event)
-        // }))
+        let token = self
+            .ime_handlers
+            .iter()
+            .find(|(_, reg)| reg.widget_id == widget)
+            .map(|(t, _)| *t)?;
+        let window_handle = self.handle.clone();
+        Some(Box::new(move |event| {
+            window_handle.update_text_field(token, event)
+        }))
     }
 
     /// Release a lock on an IME session, returning a `WidgetId` if the lock was mutable.
@@ -724,31 +969,56 @@ impl<T: Data> Window<T> {
     }
 
     fn widget_from_focus_chain(&self, forward: bool) -> Option<WidgetId> {
-        self.focus.and_then(|focus| {
-            self.focus_chain()
-                .iter()
-                // Find where the focused widget is in the focus chain
-                .position(|id| id == &focus)
-                .map(|idx| {
-                    // Return the id that's next to it in the focus chain
-                    let len = self.focus_chain().len();
-                    let new_idx = if forward {
-                        (idx + 1) % len
-                    } else {
-                        (idx + len - 1) % len
-                    };
-                    self.focus_chain()[new_idx]
-                })
-                .or_else(|| {
-                    // If the currently focused widget isn't in the focus chain,
-                    // then we'll just return the first/last entry of the chain, if any.
-                    if forward {
-                        self.focus_chain().first().copied()
-                    } else {
-                        self.focus_chain().last().copied()
-                    }
-                })
-        })
+        self.focus
+            .and_then(|focus| {
+                self.focus_chain()
+                    .iter()
+                    // Find where the focused widget is in the focus chain
+                    .position(|id| id == &focus)
+            })
+            .map(|idx| {
+                // Return the id that's next to it in the focus chain
+                let len = self.focus_chain().len();
+                let new_idx = if forward {
+                    (idx + 1) % len
+                } else {
+                    (idx + len - 1) % len
+                };
+                self.focus_chain()[new_idx]
+            })
+            .or_else(|| {
+                // If nothing is focused, or the focused widget isn't in the focus chain,
+                // then we'll just return the first/last entry of the chain, if any.
+                if forward {
+                    self.focus_chain().first().copied()
+                } else {
+                    self.focus_chain().last().copied()
+                }
+            })
+    }
+
+    /// The [`Event::FocusNext`] or [`Event::FocusPrev`] that a Tab key press turns into,
+    /// if `event` is one.
+    ///
+    /// Only Tab and Shift+Tab move the focus; Ctrl+Tab and the like are left to the
+    /// application, e.g. to switch tabs.
+    pub(crate) fn tab_focus_event(&self, event: &Event) -> Option<Event> {
+        match event {
+            Event::KeyDown(key)
+                if key.key == KbKey::Tab
+                    && !self.ime_composing
+                    && !key.mods.ctrl()
+                    && !key.mods.alt()
+                    && !key.mods.meta() =>
+            {
+                if key.mods.shift() {
+                    Some(Event::FocusPrev)
+                } else {
+                    Some(Event::FocusNext)
+                }
+            }
+            _ => None,
+        }
     }
 }
 
@@ -759,3 +1029,33 @@ impl WindowId {
         WindowId(WINDOW_COUNTER.next())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinch_zoom_accumulates_over_a_gesture() {
+        let mut zoom = PinchZoom::new(1.0);
+        let first = zoom.update(0.5, TouchPhase::Started).unwrap();
+        assert!((first - 0.5f64.exp()).abs() < 1e-9);
+        // Zooming back out by the same delta ends up where the gesture started.
+        let back = zoom.update(-0.5, TouchPhase::Moved).unwrap();
+        assert!((back - 1.0).abs() < 1e-9);
+        let end = zoom.update(0.25, TouchPhase::Ended).unwrap();
+        assert!((end - 0.25f64.exp()).abs() < 1e-9);
+
+        // The next gesture starts from scratch.
+        let next = zoom.update(0.0, TouchPhase::Started).unwrap();
+        assert!((next - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cancelled_pinch_zoom_resets() {
+        let mut zoom = PinchZoom::new(2.0);
+        zoom.update(0.5, TouchPhase::Started);
+        assert_eq!(zoom.update(0.5, TouchPhase::Cancelled), None);
+        let next = zoom.update(0.1, TouchPhase::Moved).unwrap();
+        assert!((next - 0.2f64.exp()).abs() < 1e-9);
+    }
+}